@@ -0,0 +1,263 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    AnimationDecoder, Frames,
+};
+use tauri::{AppHandle, State};
+
+use crate::image_lib::{encode_thumbnail, thumbnails_dir};
+use crate::models::DbState;
+use crate::thumb_cache_lib::stored_cache_key;
+
+// Preview hover cukup ~15 fps; frame yang lebih rapat digabung ke frame sebelumnya
+const MIN_PREVIEW_FRAME_MS: u32 = 66;
+// Lebih dari ini, frame preview dijarangkan merata di seluruh durasi
+const MAX_PREVIEW_FRAMES: usize = 120;
+// Browser memperlakukan delay GIF <= 10 ms sebagai 100 ms (khusus GIF, bukan WebP/APNG)
+const DEFAULT_GIF_FRAME_MS: u32 = 100;
+
+pub fn is_animation_candidate(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "gif" | "webp" | "png" | "apng")
+}
+
+pub struct AnimatedPreview {
+    pub webp: Vec<u8>,
+    pub frame_count: u32,
+    pub loop_count: u32, // 0 = loop terus
+    pub duration_sec: f64,
+}
+
+// Frame RGBA (sudah dikomposit ke canvas penuh) untuk GIF, WebP atau APNG animasi.
+// None kalau file hanya punya satu frame.
+fn open_frames(path: &str, ext: &str) -> Result<Option<Frames<'static>>, String> {
+    let reader = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+
+    let frames = match ext.to_lowercase().as_str() {
+        "gif" => GifDecoder::new(reader)
+            .map_err(|e| e.to_string())?
+            .into_frames(),
+        "webp" => {
+            let decoder = WebPDecoder::new(reader).map_err(|e| e.to_string())?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            decoder.into_frames()
+        }
+        _ => {
+            let decoder = PngDecoder::new(reader).map_err(|e| e.to_string())?;
+            if !decoder.is_apng().map_err(|e| e.to_string())? {
+                return Ok(None);
+            }
+            decoder.apng().map_err(|e| e.to_string())?.into_frames()
+        }
+    };
+    Ok(Some(frames))
+}
+
+// Jumlah loop dari header: GIF NETSCAPE2.0, chunk ANIM WebP, chunk acTL APNG
+fn read_loop_count(path: &str, ext: &str) -> u32 {
+    let mut bytes = Vec::new();
+    if let Ok(file) = File::open(path) {
+        // Header loop selalu ada sebelum data frame pertama
+        let _ = file.take(1024 * 1024).read_to_end(&mut bytes);
+    }
+    let find = |needle: &[u8]| bytes.windows(needle.len()).position(|w| w == needle);
+
+    match ext.to_lowercase().as_str() {
+        // "NETSCAPE2.0" 0x03 0x01 loop(u16 LE); tanpa extension = main sekali
+        "gif" => find(b"NETSCAPE2.0")
+            .and_then(|pos| bytes.get(pos + 13..pos + 15))
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+            .unwrap_or(1),
+        // "ANIM" size(4) background(4) loop(u16 LE)
+        "webp" => find(b"ANIM")
+            .and_then(|pos| bytes.get(pos + 12..pos + 14))
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as u32)
+            .unwrap_or(0),
+        // "acTL" num_frames(u32 BE) num_plays(u32 BE)
+        _ => find(b"acTL")
+            .and_then(|pos| bytes.get(pos + 8..pos + 12))
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .unwrap_or(0),
+    }
+}
+
+// Chunk RIFF (id + payload) dari file WebP hasil encode, tanpa header RIFF dan VP8X
+fn webp_frame_chunks(webp: &[u8]) -> Result<Vec<u8>, String> {
+    let mut chunks = Vec::new();
+    let mut pos = 12;
+    while pos + 8 <= webp.len() {
+        let size = u32::from_le_bytes([webp[pos + 4], webp[pos + 5], webp[pos + 6], webp[pos + 7]])
+            as usize;
+        let end = (pos + 8 + size + (size & 1)).min(webp.len());
+        if &webp[pos..pos + 4] != b"VP8X" {
+            chunks.extend_from_slice(&webp[pos..end]);
+        }
+        pos = end;
+    }
+
+    if chunks.is_empty() {
+        return Err("Frame WebP kosong".to_string());
+    }
+    Ok(chunks)
+}
+
+fn push_u24(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_le_bytes()[..3]);
+}
+
+fn push_chunk(buf: &mut Vec<u8>, id: &[u8; 4], payload: &[u8]) {
+    buf.extend_from_slice(id);
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.extend_from_slice(payload);
+    if payload.len() % 2 == 1 {
+        buf.push(0);
+    }
+}
+
+// Susun container WebP animasi: VP8X + ANIM + satu ANMF per frame (frame canvas penuh)
+fn mux_animated_webp(
+    width: u32,
+    height: u32,
+    loop_count: u32,
+    frames: &[(Vec<u8>, u32)],
+) -> Vec<u8> {
+    let mut body = b"WEBP".to_vec();
+
+    // Flag: animasi + alpha
+    let mut vp8x = vec![0x12, 0, 0, 0];
+    push_u24(&mut vp8x, width - 1);
+    push_u24(&mut vp8x, height - 1);
+    push_chunk(&mut body, b"VP8X", &vp8x);
+
+    let mut anim = vec![0, 0, 0, 0];
+    anim.extend_from_slice(&(loop_count.min(u16::MAX as u32) as u16).to_le_bytes());
+    push_chunk(&mut body, b"ANIM", &anim);
+
+    for (chunks, duration_ms) in frames {
+        let mut anmf = Vec::with_capacity(16 + chunks.len());
+        push_u24(&mut anmf, 0); // x offset
+        push_u24(&mut anmf, 0); // y offset
+        push_u24(&mut anmf, width - 1);
+        push_u24(&mut anmf, height - 1);
+        push_u24(&mut anmf, (*duration_ms).min(0xFF_FFFF));
+        anmf.push(0x02); // Tanpa blending, tanpa dispose
+        anmf.extend_from_slice(chunks);
+        push_chunk(&mut body, b"ANMF", &anmf);
+    }
+
+    let mut webp = b"RIFF".to_vec();
+    webp.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    webp.extend(body);
+    webp
+}
+
+// Gabungkan tiap dua frame preview berurutan (durasi dijumlah), supaya preview
+// tetap mencakup seluruh animasi dengan frame setengahnya
+fn halve_frames(preview: Vec<(Vec<u8>, u32)>) -> Vec<(Vec<u8>, u32)> {
+    let mut halved = Vec::with_capacity(preview.len() / 2 + 1);
+    let mut frames = preview.into_iter();
+    while let Some((frame, duration)) = frames.next() {
+        let next = frames.next().map(|(_, d)| d).unwrap_or(0);
+        halved.push((frame, duration + next));
+    }
+    halved
+}
+
+// Decode animasi frame per frame (tidak ditahan semua di memori), hitung durasi total
+// dan buat preview WebP animasi selebar target_width. None = bukan animasi.
+pub fn build_animated_preview(
+    path: &str,
+    ext: &str,
+    target_width: u32,
+    quality: f32,
+) -> Result<Option<AnimatedPreview>, String> {
+    let Some(frames) = open_frames(path, ext)? else {
+        return Ok(None);
+    };
+
+    let mut frame_count = 0u32;
+    let mut total_ms = 0u64;
+    let mut size = (0, 0);
+    let mut preview: Vec<(Vec<u8>, u32)> = Vec::new();
+    let is_gif = ext.eq_ignore_ascii_case("gif");
+    // Jarak minimum antar frame preview; digandakan tiap kali preview penuh
+    let mut min_frame_ms = MIN_PREVIEW_FRAME_MS;
+
+    for frame in frames {
+        let frame = frame.map_err(|e| e.to_string())?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay_ms = match numer / denom.max(1) {
+            ms if ms <= 10 && is_gif => DEFAULT_GIF_FRAME_MS,
+            ms => ms,
+        };
+        frame_count += 1;
+        total_ms += delay_ms as u64;
+
+        // Frame terlalu rapat: perpanjang frame sebelumnya saja
+        if let Some((_, duration)) = preview.last_mut() {
+            if *duration < min_frame_ms {
+                *duration += delay_ms;
+                continue;
+            }
+        }
+
+        let webp = encode_thumbnail(frame.buffer(), target_width, quality)?;
+        let dims = webp_dimensions(&webp)?;
+        if size == (0, 0) {
+            size = dims;
+        }
+        preview.push((webp_frame_chunks(&webp)?, delay_ms));
+
+        if preview.len() > MAX_PREVIEW_FRAMES {
+            preview = halve_frames(preview);
+            min_frame_ms *= 2;
+        }
+    }
+
+    if frame_count <= 1 {
+        return Ok(None);
+    }
+
+    let loop_count = read_loop_count(path, ext);
+    Ok(Some(AnimatedPreview {
+        webp: mux_animated_webp(size.0, size.1, loop_count, &preview),
+        frame_count,
+        loop_count,
+        duration_sec: total_ms as f64 / 1000.0,
+    }))
+}
+
+// Ukuran canvas dari WebP hasil encode (header VP8/VP8L/VP8X)
+fn webp_dimensions(webp: &[u8]) -> Result<(u32, u32), String> {
+    let decoder = WebPDecoder::new(std::io::Cursor::new(webp)).map_err(|e| e.to_string())?;
+    Ok(image::ImageDecoder::dimensions(&decoder))
+}
+
+// Preview disimpan di sebelah thumbnail: {key}_anim.webp
+pub fn animated_preview_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}_anim.webp", key))
+}
+
+#[tauri::command]
+pub fn get_animated_preview(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+) -> Result<Option<String>, String> {
+    let key = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        stored_cache_key(&conn, asset_id)?
+    };
+    let Some(key) = key else {
+        return Ok(None);
+    };
+    let path = animated_preview_path(&thumbnails_dir(&app)?, &key);
+
+    Ok(path.exists().then(|| path.to_string_lossy().to_string()))
+}
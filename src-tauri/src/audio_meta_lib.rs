@@ -0,0 +1,579 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+use symphonia::default::get_probe;
+use tauri::{AppHandle, State};
+
+use crate::{
+    models::{AssetMetadata, AudioMetadata},
+    utils::run_ffmpeg,
+    DbState,
+};
+
+// Ukuran field tetap di awal chunk bext (EBU Tech 3285)
+const BEXT_DESCRIPTION_LEN: usize = 256;
+const BEXT_MIN_LEN: usize = 602;
+
+// Satu chunk RIFF level atas. Isi chunk 'data' tidak dibaca ke memori.
+struct RiffChunk {
+    id: [u8; 4],
+    offset: u64, // posisi awal payload di file
+    size: u32,
+    payload: Option<Vec<u8>>,
+}
+
+fn read_wav_chunks(path: &Path) -> io::Result<Vec<RiffChunk>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let file_len = file.get_ref().metadata()?.len();
+
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Bukan file RIFF/WAVE",
+        ));
+    }
+
+    let mut chunks = Vec::new();
+    let mut pos = 12u64;
+
+    while pos + 8 <= file_len {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk_header = [0u8; 8];
+        file.read_exact(&mut chunk_header)?;
+
+        let id = [
+            chunk_header[0],
+            chunk_header[1],
+            chunk_header[2],
+            chunk_header[3],
+        ];
+        let size = u32::from_le_bytes([
+            chunk_header[4],
+            chunk_header[5],
+            chunk_header[6],
+            chunk_header[7],
+        ]);
+        let offset = pos + 8;
+
+        let payload = if &id == b"data" {
+            None
+        } else {
+            let mut buf = vec![0u8; (size as u64).min(file_len - offset) as usize];
+            file.read_exact(&mut buf)?;
+            Some(buf)
+        };
+
+        chunks.push(RiffChunk {
+            id,
+            offset,
+            size,
+            payload,
+        });
+
+        // Chunk RIFF selalu di-pad ke ukuran genap
+        pos = offset + size as u64 + (size as u64 & 1);
+    }
+
+    Ok(chunks)
+}
+
+fn fixed_str(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let text = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn xml_tag_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+
+    let text = xml[start..end]
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn split_keywords(text: &str) -> Vec<String> {
+    text.split([';', ','])
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+fn push_keywords(meta: &mut AudioMetadata, text: &str) {
+    for keyword in split_keywords(text) {
+        if !meta.keywords.contains(&keyword) {
+            meta.keywords.push(keyword);
+        }
+    }
+}
+
+// Sub-chunk LIST/INFO: (id, teks)
+fn parse_info_list(payload: &[u8]) -> Vec<([u8; 4], String)> {
+    let mut entries = Vec::new();
+    if payload.len() < 4 || &payload[0..4] != b"INFO" {
+        return entries;
+    }
+
+    let mut pos = 4;
+    while pos + 8 <= payload.len() {
+        let id = [
+            payload[pos],
+            payload[pos + 1],
+            payload[pos + 2],
+            payload[pos + 3],
+        ];
+        let size = u32::from_le_bytes([
+            payload[pos + 4],
+            payload[pos + 5],
+            payload[pos + 6],
+            payload[pos + 7],
+        ]) as usize;
+        let start = pos + 8;
+        let end = (start + size).min(payload.len());
+
+        if let Some(text) = fixed_str(&payload[start..end]) {
+            entries.push((id, text));
+        }
+        pos = start + size + (size & 1);
+    }
+
+    entries
+}
+
+fn apply_wav_chunks(meta: &mut AudioMetadata, chunks: &[RiffChunk]) {
+    for chunk in chunks {
+        let Some(payload) = &chunk.payload else {
+            continue;
+        };
+
+        match &chunk.id {
+            b"bext" if payload.len() >= 346 => {
+                meta.description = fixed_str(&payload[0..256]).or(meta.description.take());
+                meta.originator = fixed_str(&payload[256..288]);
+                meta.originator_reference = fixed_str(&payload[288..320]);
+
+                let date = fixed_str(&payload[320..330]);
+                let time = fixed_str(&payload[330..338]);
+                meta.origination_date = match (date, time) {
+                    (Some(date), Some(time)) => Some(format!("{} {}", date, time)),
+                    (date, _) => date,
+                };
+
+                let low =
+                    u32::from_le_bytes([payload[338], payload[339], payload[340], payload[341]]);
+                let high =
+                    u32::from_le_bytes([payload[342], payload[343], payload[344], payload[345]]);
+                meta.time_reference = Some(((high as u64) << 32) | low as u64);
+            }
+            b"iXML" => {
+                let xml = String::from_utf8_lossy(payload);
+                meta.ixml_project = xml_tag_text(&xml, "PROJECT");
+                meta.ixml_scene = xml_tag_text(&xml, "SCENE");
+                meta.ixml_take = xml_tag_text(&xml, "TAKE");
+                meta.ixml_tape = xml_tag_text(&xml, "TAPE");
+                meta.ixml_note = xml_tag_text(&xml, "NOTE");
+            }
+            b"LIST" => {
+                for (id, text) in parse_info_list(payload) {
+                    match &id {
+                        b"INAM" => meta.title = Some(text),
+                        b"IART" => meta.artist = Some(text),
+                        b"IPRD" => meta.album = Some(text),
+                        b"IGNR" => meta.genre = Some(text),
+                        b"ICMT" => meta.comment = Some(text),
+                        b"IKEY" => push_keywords(meta, &text),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn apply_tags(meta: &mut AudioMetadata, revision: &MetadataRevision) {
+    for tag in revision.tags() {
+        let value = tag.value.to_string();
+        if value.trim().is_empty() {
+            continue;
+        }
+
+        match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => meta.title = Some(value),
+            Some(StandardTagKey::Artist) => meta.artist = Some(value),
+            Some(StandardTagKey::Album) => meta.album = Some(value),
+            Some(StandardTagKey::Genre) => meta.genre = Some(value),
+            Some(StandardTagKey::Comment) => meta.comment = Some(value),
+            Some(StandardTagKey::Description) => meta.description = Some(value),
+            _ => {
+                // Keywords tidak punya key standar: Vorbis "KEYWORDS", ID3 "TXXX:keywords"
+                let key = tag.key.to_lowercase();
+                if key == "keywords" || key == "txxx:keywords" || key == "ikey" {
+                    push_keywords(meta, &value);
+                }
+            }
+        }
+    }
+}
+
+// Baca info stream + metadata embedded (ID3, Vorbis comment, RIFF INFO, bext, iXML).
+// Durasi dari header (n_frames) adalah sumber utama kolom duration_sec; 0 = tidak diketahui.
+pub fn read_audio_metadata(path: &str, file_size: i64) -> Result<(AudioMetadata, f64), String> {
+    let src = File::open(path).map_err(|e| e.to_string())?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(path).extension() {
+        hint.with_extension(&ext.to_string_lossy());
+    }
+
+    let mut probed = get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let track = probed
+        .format
+        .default_track()
+        .ok_or("No default track")?
+        .clone();
+    let params = &track.codec_params;
+
+    let sample_rate = params.sample_rate.unwrap_or(0);
+    let duration_sec = match (params.n_frames, sample_rate) {
+        (Some(frames), rate) if rate > 0 => frames as f64 / rate as f64,
+        _ => 0.0,
+    };
+    // Bitrate rata-rata dari ukuran file (kbps)
+    let bitrate = if duration_sec > 0.0 {
+        (file_size as f64 * 8.0 / duration_sec / 1000.0).round() as u32
+    } else {
+        0
+    };
+
+    let mut meta = AudioMetadata {
+        sample_rate,
+        bitrate,
+        channels: params.channels.map(|c| c.count() as u32),
+        bit_depth: params.bits_per_sample,
+        ..Default::default()
+    };
+
+    // Tag sebelum container (mis. ID3v2 di MP3) lalu tag di dalam container
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        apply_tags(&mut meta, revision);
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        apply_tags(&mut meta, revision);
+    }
+
+    // bext & iXML tidak dibaca symphonia, parse manual
+    if is_wav(path) {
+        if let Ok(chunks) = read_wav_chunks(Path::new(path)) {
+            apply_wav_chunks(&mut meta, &chunks);
+        }
+    }
+
+    // Fallback: deskripsi dari comment kalau tidak ada bext/DESCRIPTION
+    if meta.description.is_none() {
+        meta.description = meta.comment.clone();
+    }
+
+    Ok((meta, duration_sec))
+}
+
+// Gabungan teks metadata untuk kolom search_text
+pub fn audio_search_text(meta: &AudioMetadata) -> String {
+    let fields = [
+        &meta.title,
+        &meta.artist,
+        &meta.album,
+        &meta.genre,
+        &meta.comment,
+        &meta.description,
+        &meta.originator,
+        &meta.ixml_project,
+        &meta.ixml_scene,
+        &meta.ixml_note,
+    ];
+
+    let mut parts: Vec<&str> = fields.iter().filter_map(|f| f.as_deref()).collect();
+    parts.extend(meta.keywords.iter().map(|k| k.as_str()));
+    parts.join(" ")
+}
+
+fn is_wav(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("wav"))
+        .unwrap_or(false)
+}
+
+// Ekstensi file sementara saat menulis metadata; folder watcher mengabaikannya
+pub const TEMP_SUFFIX: &str = "editon-tmp";
+
+pub fn is_temp_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(TEMP_SUFFIX))
+}
+
+// Path sementara dengan ekstensi non-media supaya tidak ikut ter-import folder watcher
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(TEMP_SUFFIX);
+    path.with_file_name(name)
+}
+
+fn write_chunk(out: &mut impl Write, id: &[u8; 4], payload: &[u8]) -> io::Result<()> {
+    out.write_all(id)?;
+    out.write_all(&(payload.len() as u32).to_le_bytes())?;
+    out.write_all(payload)?;
+    if payload.len() % 2 == 1 {
+        out.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+fn build_info_list(existing: &[([u8; 4], String)], comment: &str, keywords: &str) -> Vec<u8> {
+    let mut payload = b"INFO".to_vec();
+
+    let mut entries: Vec<([u8; 4], String)> = existing
+        .iter()
+        .filter(|(id, _)| id != b"ICMT" && id != b"IKEY")
+        .cloned()
+        .collect();
+    if !comment.is_empty() {
+        entries.push((*b"ICMT", comment.to_string()));
+    }
+    if !keywords.is_empty() {
+        entries.push((*b"IKEY", keywords.to_string()));
+    }
+
+    for (id, text) in entries {
+        // Teks INFO diakhiri NUL
+        let mut bytes = text.into_bytes();
+        bytes.push(0);
+        payload.extend_from_slice(&id);
+        payload.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        payload.extend_from_slice(&bytes);
+        if bytes.len() % 2 == 1 {
+            payload.push(0);
+        }
+    }
+
+    payload
+}
+
+// Tulis ulang file WAV dengan deskripsi bext + LIST/INFO (ICMT, IKEY) yang baru
+fn write_wav_metadata(path: &Path, description: &str, keywords: &str) -> Result<(), String> {
+    let chunks = read_wav_chunks(path).map_err(|e| e.to_string())?;
+    let tmp_path = temp_path_for(path);
+
+    let result = (|| -> io::Result<()> {
+        let mut src = File::open(path)?;
+        let mut out = BufWriter::new(File::create(&tmp_path)?);
+
+        // Ukuran RIFF diisi belakangan
+        out.write_all(b"RIFF\0\0\0\0WAVE")?;
+
+        let mut wrote_bext = false;
+        let mut wrote_info = false;
+
+        // bext lama (di mana pun posisinya, bisa juga setelah data) digabung jadi satu
+        // chunk yang ditulis sekali sebelum data; chunk aslinya dilewati di loop
+        let mut bext_payload = chunks
+            .iter()
+            .find(|c| &c.id == b"bext")
+            .and_then(|c| c.payload.clone())
+            .unwrap_or_default();
+        // Pertahankan field bext lain, ganti deskripsinya saja
+        bext_payload.resize(bext_payload.len().max(BEXT_MIN_LEN), 0);
+        let desc_bytes = description.as_bytes();
+        let desc_len = desc_bytes.len().min(BEXT_DESCRIPTION_LEN);
+        bext_payload[..BEXT_DESCRIPTION_LEN].fill(0);
+        bext_payload[..desc_len].copy_from_slice(&desc_bytes[..desc_len]);
+
+        for chunk in &chunks {
+            match (&chunk.id, &chunk.payload) {
+                (b"bext", _) => {}
+                (b"LIST", Some(payload)) if payload.starts_with(b"INFO") => {
+                    let existing = parse_info_list(payload);
+                    write_chunk(
+                        &mut out,
+                        b"LIST",
+                        &build_info_list(&existing, description, keywords),
+                    )?;
+                    wrote_info = true;
+                }
+                (b"data", None) => {
+                    // bext sebaiknya ada sebelum data
+                    if !wrote_bext {
+                        write_chunk(&mut out, b"bext", &bext_payload)?;
+                        wrote_bext = true;
+                    }
+
+                    out.write_all(b"data")?;
+                    out.write_all(&chunk.size.to_le_bytes())?;
+                    src.seek(SeekFrom::Start(chunk.offset))?;
+                    io::copy(&mut (&mut src).take(chunk.size as u64), &mut out)?;
+                    if chunk.size % 2 == 1 {
+                        out.write_all(&[0])?;
+                    }
+                }
+                (id, Some(payload)) => write_chunk(&mut out, id, payload)?,
+                _ => {}
+            }
+        }
+
+        if !wrote_info {
+            write_chunk(
+                &mut out,
+                b"LIST",
+                &build_info_list(&[], description, keywords),
+            )?;
+        }
+
+        let mut file = out.into_inner().map_err(|e| e.into_error())?;
+        let riff_size = file.stream_position()? - 8;
+        file.seek(SeekFrom::Start(4))?;
+        file.write_all(&(riff_size as u32).to_le_bytes())?;
+        file.sync_all()
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(format!("Gagal menulis metadata WAV: {}", e));
+    }
+
+    std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+fn ffmpeg_muxer(ext: &str) -> &str {
+    match ext {
+        "m4a" => "ipod",
+        "aac" => "adts",
+        "wma" => "asf",
+        other => other,
+    }
+}
+
+// Selain WAV: remux tanpa re-encode lewat ffmpeg dengan tag baru
+fn write_tagged_metadata(
+    app: &AppHandle,
+    path: &Path,
+    ext: &str,
+    description: &str,
+    keywords: &str,
+) -> Result<(), String> {
+    let tmp_path = temp_path_for(path);
+
+    let args = vec![
+        "-i".to_string(),
+        path.to_string_lossy().to_string(),
+        "-map".to_string(),
+        "0".to_string(),
+        "-c".to_string(),
+        "copy".to_string(),
+        "-map_metadata".to_string(),
+        "0".to_string(),
+        "-metadata".to_string(),
+        format!("comment={}", description),
+        "-metadata".to_string(),
+        format!("description={}", description),
+        "-metadata".to_string(),
+        format!("keywords={}", keywords),
+        "-f".to_string(),
+        ffmpeg_muxer(ext).to_string(),
+        tmp_path.to_string_lossy().to_string(),
+    ];
+
+    if let Err(e) = run_ffmpeg(app, &args) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    std::fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn write_audio_metadata(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+    description: String,
+    keywords: Vec<String>,
+) -> Result<AudioMetadata, String> {
+    let (path, extension): (String, String) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT original_path, extension FROM assets WHERE id = ?1 AND type = 'audio'",
+            [asset_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Asset tidak ditemukan: {}", e))?
+    };
+
+    let keywords = keywords
+        .iter()
+        .map(|k| k.trim())
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let ext = extension.to_lowercase();
+
+    let task_path = path.clone();
+    let (meta, duration_sec) = tauri::async_runtime::spawn_blocking(move || {
+        let target = Path::new(&task_path);
+        if ext == "wav" {
+            write_wav_metadata(target, &description, &keywords)?;
+        } else {
+            write_tagged_metadata(&app, target, &ext, &description, &keywords)?;
+        }
+
+        let file_size = std::fs::metadata(target)
+            .map(|m| m.len() as i64)
+            .unwrap_or(0);
+        read_audio_metadata(&task_path, file_size)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let search_text = audio_search_text(&meta);
+    let metadata_json = serde_json::to_string(&AssetMetadata::Audio(Box::new(meta.clone())))
+        .unwrap_or("{}".to_string());
+
+    {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE assets SET metadata = ?1, search_text = ?2,
+                    duration_sec = CASE WHEN ?3 > 0 THEN ?3 ELSE duration_sec END
+             WHERE id = ?4",
+            rusqlite::params![metadata_json, search_text, duration_sec, asset_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(meta)
+}
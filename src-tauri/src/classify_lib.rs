@@ -0,0 +1,231 @@
+use tauri::State;
+
+use crate::{
+    feature_lib::{load_all_features, AudioFeatures},
+    models::AssetTag,
+    DbState,
+};
+
+// Sumber tag: 'auto' dari classifier, 'user' dari input manual
+const TAG_SOURCE_AUTO: &str = "auto";
+const TAG_SOURCE_USER: &str = "user";
+// Saran di bawah confidence ini tidak disimpan
+const MIN_CONFIDENCE: f32 = 0.2;
+const MAX_SUGGESTIONS: usize = 2;
+
+// 0 saat x <= lo, naik linear ke 1 saat x >= hi (kebalikannya kalau lo > hi)
+fn ramp(x: f32, lo: f32, hi: f32) -> f32 {
+    ((x - lo) / (hi - lo)).clamp(0.0, 1.0)
+}
+
+// 1 di dalam rentang [lo, hi], turun linear ke 0 sejauh `soft` di luar rentang
+fn band(x: f32, lo: f32, hi: f32, soft: f32) -> f32 {
+    ramp(x, lo - soft, lo).min(ramp(x, hi + soft, hi))
+}
+
+// Classifier berbasis aturan sederhana di atas fitur spektral.
+// Return (kategori, confidence) urut dari confidence tertinggi.
+pub fn classify_features(f: &AudioFeatures) -> Vec<(&'static str, f32)> {
+    let d = f.duration_sec;
+    let noisy = ramp(f.spectral_flatness, 0.05, 0.3);
+    let tonal = 1.0 - noisy;
+    // Dinamika tinggi = transien/suku kata, rendah = suara stabil
+    let dynamic = ramp(f.rms_db_std, 4.0, 12.0);
+    let steady = 1.0 - dynamic;
+    let sweeping = ramp(f.spectral_centroid_std, 300.0, 1500.0);
+
+    let scores = [
+        (
+            "impact",
+            ramp(d, 6.0, 2.0) * dynamic * ramp(f.spectral_flatness, 0.01, 0.1),
+        ),
+        ("whoosh", band(d, 0.3, 3.0, 1.5) * noisy * sweeping),
+        ("ambience", ramp(d, 4.0, 12.0) * noisy * steady),
+        (
+            "ui",
+            ramp(d, 1.5, 0.5) * tonal * band(f.spectral_centroid, 800.0, 6000.0, 800.0),
+        ),
+        (
+            "foley",
+            ramp(d, 12.0, 4.0)
+                * band(f.spectral_centroid, 500.0, 5000.0, 1000.0)
+                * band(f.spectral_flatness, 0.02, 0.25, 0.1),
+        ),
+        ("music", ramp(d, 6.0, 20.0) * tonal * (0.5 + 0.5 * steady)),
+        (
+            "voice",
+            band(d, 0.5, 30.0, 0.5)
+                * band(f.spectral_centroid, 300.0, 3000.0, 500.0)
+                * dynamic
+                * ramp(f.zero_crossing_rate_std, 0.01, 0.05),
+        ),
+    ];
+
+    let total: f32 = scores.iter().map(|(_, s)| s).sum();
+    if total <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut result: Vec<(&'static str, f32)> = scores
+        .iter()
+        // Confidence relatif terhadap kategori lain, dikali skor mentah agar
+        // file yang tidak cocok ke mana-mana tetap berconfidence rendah
+        .map(|(tag, s)| (*tag, (s / total) * s.sqrt()))
+        .collect();
+    result.sort_by(|a, b| b.1.total_cmp(&a.1));
+    result
+}
+
+#[tauri::command]
+pub fn classify_audio_assets(state: State<'_, DbState>, overwrite: bool) -> Result<String, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let all = load_all_features(&conn)?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    if overwrite {
+        // Tag yang sudah diterima/ditolak user tetap dipertahankan
+        tx.execute(
+            "DELETE FROM asset_tags WHERE source = ?1 AND status = 'suggested'",
+            [TAG_SOURCE_AUTO],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    let mut classified = 0;
+    {
+        let mut has_auto = tx
+            .prepare_cached(
+                "SELECT EXISTS(SELECT 1 FROM asset_tags WHERE asset_id = ?1 AND source = ?2)",
+            )
+            .map_err(|e| e.to_string())?;
+        let mut insert = tx
+            .prepare_cached(
+                "INSERT OR IGNORE INTO asset_tags (asset_id, tag, source, confidence, status)
+                 VALUES (?1, ?2, ?3, ?4, 'suggested')",
+            )
+            .map_err(|e| e.to_string())?;
+
+        for (asset_id, features) in &all {
+            let already: bool = has_auto
+                .query_row(rusqlite::params![asset_id, TAG_SOURCE_AUTO], |row| {
+                    row.get(0)
+                })
+                .map_err(|e| e.to_string())?;
+            if already && !overwrite {
+                continue;
+            }
+
+            for (tag, confidence) in classify_features(features)
+                .into_iter()
+                .filter(|(_, c)| *c >= MIN_CONFIDENCE)
+                .take(MAX_SUGGESTIONS)
+            {
+                insert
+                    .execute(rusqlite::params![
+                        asset_id,
+                        tag,
+                        TAG_SOURCE_AUTO,
+                        confidence
+                    ])
+                    .map_err(|e| e.to_string())?;
+            }
+            classified += 1;
+        }
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "Klasifikasi selesai untuk {} file audio",
+        classified
+    ))
+}
+
+#[tauri::command]
+pub fn get_asset_tags(state: State<'_, DbState>, asset_id: i64) -> Result<Vec<AssetTag>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, asset_id, tag, source, confidence, status FROM asset_tags
+             WHERE asset_id = ?1 ORDER BY confidence DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([asset_id], |row| {
+            Ok(AssetTag {
+                id: row.get("id")?,
+                asset_id: row.get("asset_id")?,
+                tag: row.get("tag")?,
+                source: row.get("source")?,
+                confidence: row.get("confidence")?,
+                status: row.get("status")?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+// Terima atau tolak saran tag dari classifier
+#[tauri::command]
+pub fn set_tag_status(
+    state: State<'_, DbState>,
+    tag_id: i64,
+    accepted: bool,
+) -> Result<String, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let status = if accepted { "accepted" } else { "rejected" };
+    let updated = conn
+        .execute(
+            "UPDATE asset_tags SET status = ?1 WHERE id = ?2",
+            rusqlite::params![status, tag_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err("Tag tidak ditemukan".to_string());
+    }
+
+    Ok(format!("Tag {}", status))
+}
+
+// Tag manual dari user langsung berstatus accepted. Kalau tag yang sama sudah ada
+// sebagai saran classifier, saran itu diambil alih jadi tag user.
+#[tauri::command]
+pub fn add_asset_tag(
+    state: State<'_, DbState>,
+    asset_id: i64,
+    tag: String,
+) -> Result<AssetTag, String> {
+    let tag = tag.trim().to_lowercase();
+    if tag.is_empty() {
+        return Err("Tag tidak boleh kosong".to_string());
+    }
+
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    conn.query_row(
+        "INSERT INTO asset_tags (asset_id, tag, source, confidence, status)
+         VALUES (?1, ?2, ?3, 1.0, 'accepted')
+         ON CONFLICT(asset_id, tag) DO UPDATE SET
+            source = excluded.source, confidence = 1.0, status = 'accepted'
+         RETURNING id, asset_id, tag, source, confidence, status",
+        rusqlite::params![asset_id, tag, TAG_SOURCE_USER],
+        |row| {
+            Ok(AssetTag {
+                id: row.get("id")?,
+                asset_id: row.get("asset_id")?,
+                tag: row.get("tag")?,
+                source: row.get("source")?,
+                confidence: row.get("confidence")?,
+                status: row.get("status")?,
+            })
+        },
+    )
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,213 @@
+use image::RgbaImage;
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::models::{DbState, PaletteColor};
+
+pub const PALETTE_SIZE: usize = 5;
+// Warna dengan porsi lebih kecil dari ini dianggap aksen, tidak ikut disimpan
+const MIN_COLOR_WEIGHT: f32 = 0.03;
+// Cukup ~4000 sampel untuk palette yang stabil
+const MAX_SAMPLES: u32 = 4096;
+const KMEANS_ITERATIONS: usize = 12;
+// Default jarak ΔE (CIE76) untuk filter warna; ~10 masih terlihat "warna yang sama"
+pub const DEFAULT_COLOR_DISTANCE: f64 = 20.0;
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// sRGB -> CIELAB (D65). Jarak Euclid di Lab mendekati beda warna yang dilihat mata.
+pub fn rgb_to_lab(r: u8, g: u8, b: u8) -> [f32; 3] {
+    let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn distance_sq(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// "#ff8800" / "ff8800" -> (r, g, b)
+pub fn parse_hex_color(hex: &str) -> Result<(u8, u8, u8), String> {
+    let hex = hex.trim().trim_start_matches('#');
+    // Cek digit ASCII dulu: len() menghitung byte, slicing karakter multibyte bisa panic
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Warna tidak valid: {}", hex));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("Warna tidak valid: {}", hex))
+    };
+    Ok((channel(0)?, channel(2)?, channel(4)?))
+}
+
+// Palette dominan dengan k-means di ruang Lab, diurutkan dari porsi terbesar.
+// Pixel transparan diabaikan.
+pub fn dominant_colors(img: &RgbaImage, count: usize) -> Vec<PaletteColor> {
+    let step = ((img.width() as u64 * img.height() as u64) as f64 / MAX_SAMPLES as f64)
+        .sqrt()
+        .max(1.0) as u32;
+
+    let samples: Vec<([f32; 3], [u8; 3])> = (0..img.height())
+        .step_by(step as usize)
+        .flat_map(|y| (0..img.width()).step_by(step as usize).map(move |x| (x, y)))
+        .map(|(x, y)| img.get_pixel(x, y).0)
+        .filter(|p| p[3] >= 128)
+        .map(|p| (rgb_to_lab(p[0], p[1], p[2]), [p[0], p[1], p[2]]))
+        .collect();
+
+    if samples.is_empty() {
+        return Vec::new();
+    }
+
+    // Init k-means++ deterministik: mulai dari sampel pertama, lalu selalu ambil
+    // sampel terjauh dari centroid yang sudah ada
+    let mut centroids = vec![samples[0].0];
+    while centroids.len() < count.min(samples.len()) {
+        let farthest = samples
+            .iter()
+            .max_by(|a, b| {
+                let da = centroids
+                    .iter()
+                    .map(|c| distance_sq(&a.0, c))
+                    .fold(f32::MAX, f32::min);
+                let db = centroids
+                    .iter()
+                    .map(|c| distance_sq(&b.0, c))
+                    .fold(f32::MAX, f32::min);
+                da.total_cmp(&db)
+            })
+            .map(|s| s.0);
+        match farthest {
+            Some(lab) if !centroids.contains(&lab) => centroids.push(lab),
+            _ => break, // Warna unik lebih sedikit dari count
+        }
+    }
+
+    let nearest = |lab: &[f32; 3], centroids: &[[f32; 3]]| {
+        centroids
+            .iter()
+            .enumerate()
+            .min_by(|a, b| distance_sq(lab, a.1).total_cmp(&distance_sq(lab, b.1)))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    };
+
+    let mut sums = vec![([0f64; 3], [0u64; 3], 0usize); centroids.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        sums.iter_mut().for_each(|s| *s = ([0.0; 3], [0; 3], 0));
+        for (lab, rgb) in &samples {
+            let sum = &mut sums[nearest(lab, &centroids)];
+            for c in 0..3 {
+                sum.0[c] += lab[c] as f64;
+                sum.1[c] += rgb[c] as u64;
+            }
+            sum.2 += 1;
+        }
+
+        for (centroid, (lab_sum, _, n)) in centroids.iter_mut().zip(&sums) {
+            if *n > 0 {
+                *centroid = lab_sum.map(|v| (v / *n as f64) as f32);
+            }
+        }
+    }
+
+    let total = samples.len() as f32;
+    let mut palette: Vec<PaletteColor> = sums
+        .iter()
+        .filter(|(_, _, n)| *n > 0)
+        .map(|(_, rgb_sum, n)| {
+            let [r, g, b] = rgb_sum.map(|v| (v / *n as u64) as u8);
+            PaletteColor {
+                hex: format!("#{:02x}{:02x}{:02x}", r, g, b),
+                weight: *n as f32 / total,
+            }
+        })
+        .filter(|c| c.weight >= MIN_COLOR_WEIGHT)
+        .collect();
+
+    palette.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    palette
+}
+
+// Simpan palette (nilai Lab ikut disimpan untuk filter jarak di SQL)
+pub fn replace_palette(
+    conn: &Connection,
+    asset_id: i64,
+    palette: &[PaletteColor],
+) -> Result<(), String> {
+    conn.execute("DELETE FROM asset_colors WHERE asset_id = ?1", [asset_id])
+        .map_err(|e| e.to_string())?;
+
+    for (index, color) in palette.iter().enumerate() {
+        let (r, g, b) = parse_hex_color(&color.hex)?;
+        let [l, a, lab_b] = rgb_to_lab(r, g, b);
+        conn.execute(
+            "INSERT INTO asset_colors (asset_id, color_index, hex, lab_l, lab_a, lab_b, weight)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![asset_id, index as i64, color.hex, l, a, lab_b, color.weight],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_asset_palette(
+    state: State<'_, DbState>,
+    asset_id: i64,
+) -> Result<Vec<PaletteColor>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT hex, weight FROM asset_colors WHERE asset_id = ?1 ORDER BY color_index")
+        .map_err(|e| e.to_string())?;
+
+    let palette = stmt
+        .query_map([asset_id], |row| {
+            Ok(PaletteColor {
+                hex: row.get(0)?,
+                weight: row.get(1)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_color_with_and_without_hash() {
+        assert_eq!(parse_hex_color("#ff8000"), Ok((255, 128, 0)));
+        assert_eq!(parse_hex_color(" 00AAff "), Ok((0, 170, 255)));
+    }
+
+    #[test]
+    fn hex_color_rejects_invalid_input() {
+        assert!(parse_hex_color("#fff").is_err());
+        assert!(parse_hex_color("#gg0000").is_err());
+        assert!(parse_hex_color("#+1+1+1").is_err());
+        // Multibyte: 6 byte tapi bukan 6 digit, tidak boleh panic
+        assert!(parse_hex_color("#ééé").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    fs::File,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Deserialize;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::sample::SampleFormat;
+use symphonia::default::get_probe;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+    folder_lib::import_single_file, models::ProgressEvent, sound_lib::build_export_path,
+    utils::run_ffmpeg, DbState,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConvertOptions {
+    pub format: String, // 'wav', 'flac', 'aiff', 'mp3', 'ogg', 'm4a'
+    pub sample_rate: Option<u32>,
+    pub bit_depth: Option<u16>, // Hanya untuk format PCM/FLAC; None = ikut sumber, 32 = float
+    pub channels: Option<u16>,
+    pub output_dir: Option<String>,
+}
+
+// Bit depth PCM tujuan; float hanya berlaku untuk 32-bit
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PcmDepth {
+    bits: u16,
+    float: bool,
+}
+
+// Sumber lossy (mp3/ogg/aac) tidak punya bit depth, hasil decode-nya disimpan 24-bit
+const DEFAULT_PCM_DEPTH: PcmDepth = PcmDepth {
+    bits: 24,
+    float: false,
+};
+
+// Pilihan bit depth dari user: 32-bit selalu float (WAV maupun AIFF)
+fn requested_depth(bits: u16) -> PcmDepth {
+    PcmDepth {
+        bits,
+        float: bits == 32,
+    }
+}
+
+fn is_lossless(format: &str) -> bool {
+    matches!(format, "wav" | "aiff" | "flac")
+}
+
+// Bit depth file sumber dari header (tanpa decode), dibulatkan ke depth PCM standar
+fn source_depth(path: &str) -> Option<PcmDepth> {
+    let src = File::open(path).ok()?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(path).extension() {
+        hint.with_extension(&ext.to_string_lossy());
+    }
+
+    let probed = get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+    let params = &probed.format.default_track()?.codec_params;
+
+    if matches!(
+        params.sample_format,
+        Some(SampleFormat::F32) | Some(SampleFormat::F64)
+    ) {
+        return Some(PcmDepth {
+            bits: 32,
+            float: true,
+        });
+    }
+
+    let bits = match params.bits_per_sample? {
+        0..=8 => 8,
+        9..=16 => 16,
+        17..=24 => 24,
+        _ => 32,
+    };
+    Some(PcmDepth { bits, float: false })
+}
+
+// Depth tujuan: pilihan user (32 = float), atau ikut sumber supaya file 24-bit
+// tidak diam-diam jadi 16-bit saat hanya ganti sample rate
+fn target_depth(format: &str, bit_depth: Option<u16>, path: &str) -> Option<PcmDepth> {
+    if !is_lossless(format) {
+        return None;
+    }
+
+    Some(match bit_depth {
+        Some(bits) => requested_depth(bits),
+        None => {
+            let depth = source_depth(path).unwrap_or(DEFAULT_PCM_DEPTH);
+            // FLAC maksimal 24-bit
+            if format == "flac" && depth.bits > 24 {
+                DEFAULT_PCM_DEPTH
+            } else {
+                depth
+            }
+        }
+    })
+}
+
+// Argumen codec ffmpeg sesuai format & bit depth tujuan
+fn codec_args(format: &str, depth: Option<PcmDepth>) -> Result<Vec<String>, String> {
+    let bits = depth.map(|d| (d.bits, d.float));
+    let args: Vec<&str> = match (format, bits) {
+        ("wav", Some((8, _))) => vec!["-c:a", "pcm_u8"],
+        ("wav", Some((16, _))) => vec!["-c:a", "pcm_s16le"],
+        ("wav", Some((24, _))) => vec!["-c:a", "pcm_s24le"],
+        ("wav", Some((32, true))) => vec!["-c:a", "pcm_f32le"],
+        ("wav", Some((32, false))) => vec!["-c:a", "pcm_s32le"],
+        ("aiff", Some((8, _))) => vec!["-c:a", "pcm_s8"],
+        ("aiff", Some((16, _))) => vec!["-c:a", "pcm_s16be"],
+        ("aiff", Some((24, _))) => vec!["-c:a", "pcm_s24be"],
+        ("aiff", Some((32, true))) => vec!["-c:a", "pcm_f32be"],
+        ("aiff", Some((32, false))) => vec!["-c:a", "pcm_s32be"],
+        ("flac", Some((8 | 16, _))) => vec!["-c:a", "flac", "-sample_fmt", "s16"],
+        ("flac", Some((24, _))) => vec![
+            "-c:a",
+            "flac",
+            "-sample_fmt",
+            "s32",
+            "-bits_per_raw_sample",
+            "24",
+        ],
+        ("mp3", _) => vec!["-c:a", "libmp3lame", "-q:a", "2"],
+        ("ogg", _) => vec!["-c:a", "libvorbis", "-q:a", "6"],
+        ("m4a", _) => vec!["-c:a", "aac", "-b:a", "256k"],
+        (format, Some((bits, _))) => {
+            return Err(format!(
+                "Bit depth {} tidak didukung untuk format {}",
+                bits, format
+            ))
+        }
+        (format, None) => return Err(format!("Format {} tidak didukung", format)),
+    };
+
+    Ok(args.into_iter().map(String::from).collect())
+}
+
+// Suffix nama file dari opsi konversi, mis. "48k_24bit_mono"
+fn output_suffix(options: &ConvertOptions) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(rate) = options.sample_rate {
+        if rate % 1000 == 0 {
+            parts.push(format!("{}k", rate / 1000));
+        } else {
+            parts.push(format!("{:.1}k", rate as f64 / 1000.0));
+        }
+    }
+    if let Some(bits) = options.bit_depth {
+        parts.push(format!("{}bit", bits));
+    }
+    match options.channels {
+        Some(1) => parts.push("mono".to_string()),
+        Some(2) => parts.push("stereo".to_string()),
+        Some(n) => parts.push(format!("{}ch", n)),
+        None => {}
+    }
+
+    if parts.is_empty() {
+        "converted".to_string()
+    } else {
+        parts.join("_")
+    }
+}
+
+pub fn convert_file(
+    app: &AppHandle,
+    input: &str,
+    output: &Path,
+    options: &ConvertOptions,
+) -> Result<(), String> {
+    let mut args = vec![
+        "-i".to_string(),
+        input.to_string(),
+        "-map".to_string(),
+        "0:a:0".to_string(),
+        "-map_metadata".to_string(),
+        "0".to_string(),
+    ];
+
+    if let Some(rate) = options.sample_rate {
+        args.extend(["-ar".to_string(), rate.to_string()]);
+    }
+    if let Some(channels) = options.channels {
+        args.extend(["-ac".to_string(), channels.to_string()]);
+    }
+
+    let depth = target_depth(&options.format, options.bit_depth, input);
+    args.extend(codec_args(&options.format, depth)?);
+    args.push(output.to_string_lossy().to_string());
+
+    run_ffmpeg(app, &args)
+}
+
+#[tauri::command]
+pub fn convert_audio(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Vec<i64>,
+    options: ConvertOptions,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let options = ConvertOptions {
+        format: options.format.to_lowercase(),
+        ..options
+    };
+
+    // Validasi di awal supaya error langsung terlihat di UI
+    let depth = match options.bit_depth {
+        Some(bits) => Some(requested_depth(bits)),
+        None => is_lossless(&options.format).then_some(DEFAULT_PCM_DEPTH),
+    };
+    codec_args(&options.format, depth)?;
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT id, original_path, filename FROM assets WHERE id = ?1")
+            .map_err(|e| e.to_string())?;
+
+        asset_ids
+            .iter()
+            .filter_map(|id| {
+                stmt.query_row([id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+                    .ok()
+            })
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Tidak ada file untuk dikonversi.".to_string());
+    }
+
+    let suffix = output_suffix(&options);
+    let format = options.format.clone();
+    let processed_count = std::sync::Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(_id, path, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "convert-progress",
+                ProgressEvent {
+                    name: "Convert".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let result =
+                build_export_path(path, options.output_dir.clone(), &suffix, &options.format)
+                    .and_then(|out_path| {
+                        convert_file(&app, path, &out_path, &options)?;
+                        import_single_file(&db_arc, &out_path)
+                    });
+
+            if let Err(e) = result {
+                println!("Gagal konversi {}: {}", filename, e);
+            }
+        });
+
+        let _ = app.emit(
+            "convert-progress",
+            ProgressEvent {
+                name: "Convert".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai konversi {} file ke {}...",
+        total_files, format
+    ))
+}
@@ -1,8 +1,53 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, Row};
 use std::collections::HashSet;
-use tauri::State;
+use tauri::{AppHandle, State};
 
-use crate::models::DbState;
+use crate::models::{Asset, AssetMetadata, DbState, UcsInfo};
+use crate::proxy_lib::{collect_proxy_garbage, proxy_dir};
+
+// Kolom yang dibaca asset_from_row, pakai di SELECT supaya urutannya konsisten
+pub const ASSET_COLUMNS: &str = "id, filename, extension, original_path, type,
+                thumbnail_path, duration_sec, file_size, waveform_data, waveform_rms, metadata,
+                trim_start_sec, trim_end_sec, ucs_cat_id, ucs_category, ucs_subcategory,
+                ucs_fx_name, ucs_creator_id, ucs_source_id, source_asset_id, rating, blurhash";
+
+pub fn asset_from_row(row: &Row) -> rusqlite::Result<Asset> {
+    let waveform_str: String = row.get("waveform_data").unwrap_or("[]".to_string());
+    let waveform_rms_str: String = row.get("waveform_rms").unwrap_or("[]".to_string());
+    let metadata_str: String = row.get("metadata").unwrap_or("{}".to_string());
+
+    let ucs = match row.get::<_, Option<String>>("ucs_cat_id")? {
+        Some(cat_id) => Some(UcsInfo {
+            cat_id,
+            category: row.get("ucs_category")?,
+            subcategory: row.get("ucs_subcategory")?,
+            fx_name: row.get("ucs_fx_name")?,
+            creator_id: row.get("ucs_creator_id")?,
+            source_id: row.get("ucs_source_id")?,
+        }),
+        None => None,
+    };
+
+    Ok(Asset {
+        id: row.get("id")?,
+        filename: row.get("filename")?,
+        extension: row.get("extension")?,
+        original_path: row.get("original_path")?,
+        type_name: row.get("type")?,
+        thumbnail_path: row.get("thumbnail_path")?,
+        duration_sec: row.get("duration_sec")?,
+        file_size: row.get("file_size")?,
+        waveform_data: serde_json::from_str(&waveform_str).unwrap_or_default(),
+        waveform_rms: serde_json::from_str(&waveform_rms_str).unwrap_or_default(),
+        metadata: serde_json::from_str(&metadata_str).unwrap_or(AssetMetadata::None),
+        trim_start_sec: row.get("trim_start_sec")?,
+        trim_end_sec: row.get("trim_end_sec")?,
+        ucs,
+        source_asset_id: row.get("source_asset_id")?,
+        rating: row.get("rating")?,
+        blurhash: row.get("blurhash")?,
+    })
+}
 
 pub fn is_schema_valid(conn: &Connection) -> bool {
     // Ambil info kolom dari tabel assets
@@ -32,7 +77,21 @@ pub fn is_schema_valid(conn: &Connection) -> bool {
         "duration_sec",
         "file_size",
         "waveform_data",
+        "waveform_rms",
         "metadata",
+        "trim_start_sec",
+        "trim_end_sec",
+        "ucs_cat_id",
+        "ucs_category",
+        "ucs_subcategory",
+        "ucs_fx_name",
+        "ucs_creator_id",
+        "ucs_source_id",
+        "search_text",
+        "source_asset_id",
+        "rating",
+        "blurhash",
+        "thumbnail_key",
     ];
     let expected_columns: HashSet<String> = expected_columns_vec
         .into_iter()
@@ -49,7 +108,7 @@ pub fn is_schema_valid(conn: &Connection) -> bool {
 }
 
 #[tauri::command]
-pub fn clear_db(state: State<'_, DbState>) -> Result<String, String> {
+pub fn clear_db(app: AppHandle, state: State<'_, DbState>) -> Result<String, String> {
     let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
 
     let tx = conn.transaction().map_err(|e| e.to_string())?;
@@ -57,10 +116,43 @@ pub fn clear_db(state: State<'_, DbState>) -> Result<String, String> {
     tx.execute("DELETE FROM assets", [])
         .map_err(|e| e.to_string())?;
 
-    tx.execute("DELETE FROM sqlite_sequence WHERE name='assets'", [])
-        .map_err(|e| e.to_string())?;
+    // Region, shot, fitur & tag ikut terhapus lewat ON DELETE CASCADE
+    tx.execute(
+        "DELETE FROM sqlite_sequence WHERE name IN ('assets', 'regions', 'shots', 'asset_tags')",
+        [],
+    )
+    .map_err(|e| e.to_string())?;
 
     tx.commit().map_err(|e| e.to_string())?;
 
+    // Baris proxy sudah terhapus; file-nya dibuang juga
+    collect_proxy_garbage(&proxy_dir(&app)?, &conn)?;
+
     Ok("Database cleared".to_string())
 }
+
+// Rating 0-5 (-1 = rejected), None untuk menghapus rating
+#[tauri::command]
+pub fn set_asset_rating(
+    state: State<'_, DbState>,
+    asset_id: i64,
+    rating: Option<i32>,
+) -> Result<String, String> {
+    if rating.is_some_and(|r| !(-1..=5).contains(&r)) {
+        return Err("Rating harus di antara -1 dan 5".to_string());
+    }
+
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let updated = conn
+        .execute(
+            "UPDATE assets SET rating = ?1 WHERE id = ?2",
+            rusqlite::params![rating, asset_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err("Asset tidak ditemukan".to_string());
+    }
+
+    Ok("Rating disimpan".to_string())
+}
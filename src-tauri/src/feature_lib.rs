@@ -0,0 +1,483 @@
+use std::{
+    collections::HashMap,
+    f32::consts::PI,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rusqlite::Connection;
+use rustfft::{num_complex::Complex, FftPlanner};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+    db_lib::{asset_from_row, ASSET_COLUMNS},
+    models::{ProgressEvent, SimilarAsset},
+    sound_lib::{decode_audio_limited, DecodedAudio},
+    DbState,
+};
+
+const FRAME_SIZE: usize = 2048;
+const HOP_SIZE: usize = 1024;
+const NUM_MEL_BANDS: usize = 26;
+const NUM_MFCC: usize = 13;
+const ROLLOFF_RATIO: f32 = 0.85;
+// Frame lebih pelan dari ini dianggap hening dan tidak ikut statistik spektral
+const SILENT_FRAME_DB: f32 = -60.0;
+// Ambience panjang cukup dianalisis sebagian awalnya saja
+const MAX_ANALYSIS_SEC: f64 = 30.0;
+
+// Ringkasan spektral satu file audio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioFeatures {
+    pub mfcc_mean: Vec<f32>,
+    pub mfcc_std: Vec<f32>,
+    pub spectral_centroid: f32, // Hz
+    pub spectral_centroid_std: f32,
+    pub spectral_rolloff: f32,  // Hz
+    pub spectral_flatness: f32, // 0 (tonal) s/d 1 (noise)
+    pub zero_crossing_rate: f32,
+    pub zero_crossing_rate_std: f32,
+    pub rms_db: f32,
+    pub rms_db_std: f32,
+    pub duration_sec: f32,
+}
+
+impl AudioFeatures {
+    // Vektor datar untuk nearest-neighbour search
+    pub fn to_vector(&self) -> Vec<f32> {
+        let mut v = Vec::with_capacity(NUM_MFCC * 2 + 9);
+        v.extend_from_slice(&self.mfcc_mean);
+        v.extend_from_slice(&self.mfcc_std);
+        v.push(self.spectral_centroid);
+        v.push(self.spectral_centroid_std);
+        v.push(self.spectral_rolloff);
+        v.push(self.spectral_flatness);
+        v.push(self.zero_crossing_rate);
+        v.push(self.zero_crossing_rate_std);
+        v.push(self.rms_db);
+        v.push(self.rms_db_std);
+        // Durasi pakai skala log supaya 1 detik vs 2 detik sebanding dengan 30 vs 60 detik
+        v.push((1.0 + self.duration_sec).ln());
+        v
+    }
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+// Filterbank segitiga, satu baris bobot per band untuk bin 0..=FRAME_SIZE/2
+fn mel_filterbank(sample_rate: u32) -> Vec<Vec<f32>> {
+    let num_bins = FRAME_SIZE / 2 + 1;
+    let max_mel = hz_to_mel(sample_rate as f32 / 2.0);
+    let bin_hz = sample_rate as f32 / FRAME_SIZE as f32;
+
+    let edges: Vec<f32> = (0..NUM_MEL_BANDS + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (NUM_MEL_BANDS + 1) as f32))
+        .collect();
+
+    (0..NUM_MEL_BANDS)
+        .map(|band| {
+            let (lo, mid, hi) = (edges[band], edges[band + 1], edges[band + 2]);
+            (0..num_bins)
+                .map(|bin| {
+                    let hz = bin as f32 * bin_hz;
+                    if hz <= lo || hz >= hi {
+                        0.0
+                    } else if hz <= mid {
+                        (hz - lo) / (mid - lo)
+                    } else {
+                        (hi - hz) / (hi - mid)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn mean_std(values: &[f32]) -> (f32, f32) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = values.len() as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    (mean, var.sqrt())
+}
+
+// Durasi untuk fitur: dari header (total_frames) atau kolom duration_sec asset.
+// Kalau dua-duanya tidak ada, panjang hasil decode dipakai: tepat kalau decode tidak
+// terpotong MAX_ANALYSIS_SEC, dan tetap batas bawah yang lebih baik dari 0 kalau terpotong.
+fn feature_duration(audio: &DecodedAudio, stored_duration: f64) -> f64 {
+    match audio.total_frames {
+        Some(_) => audio.total_duration_sec(),
+        None if stored_duration > 0.0 => stored_duration,
+        None => audio.duration_sec(),
+    }
+}
+
+// Hitung & simpan fitur satu file audio. Dipanggil dari pass analisis audio
+// (update_waveform_and_metadata) setelah durasi header tersimpan, dan dari
+// generate_missing_features untuk asset lama yang belum punya fitur.
+pub fn update_audio_features(
+    db_arc: &Arc<Mutex<Connection>>,
+    asset_id: i64,
+    path: &str,
+    filename: &str,
+) {
+    let stored_duration: f64 = db_arc
+        .lock()
+        .ok()
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT COALESCE(duration_sec, 0) FROM assets WHERE id = ?1",
+                [asset_id],
+                |row| row.get(0),
+            )
+            .ok()
+        })
+        .unwrap_or(0.0);
+
+    let features = decode_audio_limited(path, Some(MAX_ANALYSIS_SEC))
+        .map_err(|e| e.to_string())
+        .and_then(|audio| {
+            extract_features(&audio, feature_duration(&audio, stored_duration))
+                .ok_or("Audio kosong".to_string())
+        });
+
+    match features {
+        Ok(features) => {
+            let json = serde_json::to_string(&features).unwrap_or("{}".to_string());
+
+            if let Ok(conn) = db_arc.lock() {
+                let _ = conn.execute(
+                    "INSERT OR REPLACE INTO audio_features (asset_id, features)
+                     VALUES (?1, ?2)",
+                    rusqlite::params![asset_id, json],
+                );
+            }
+        }
+        Err(e) => {
+            println!("Gagal ekstrak fitur {}: {}", filename, e);
+        }
+    }
+}
+
+pub fn extract_features(audio: &DecodedAudio, duration_sec: f64) -> Option<AudioFeatures> {
+    if audio.sample_rate == 0 || audio.samples.is_empty() {
+        return None;
+    }
+
+    let mono = audio.to_mono();
+    let rate = audio.sample_rate as f32;
+    let bin_hz = rate / FRAME_SIZE as f32;
+    let filterbank = mel_filterbank(audio.sample_rate);
+
+    let window: Vec<f32> = (0..FRAME_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FRAME_SIZE as f32).cos())
+        .collect();
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FRAME_SIZE);
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); FRAME_SIZE];
+
+    let mut mfccs: Vec<[f32; NUM_MFCC]> = Vec::new();
+    let mut centroids = Vec::new();
+    let mut rolloffs = Vec::new();
+    let mut flatnesses = Vec::new();
+    let mut zcrs = Vec::new();
+    let mut rms_values = Vec::new();
+
+    // File yang lebih pendek dari satu frame tetap dianalisis (sisanya zero padding)
+    let num_frames = if mono.len() <= FRAME_SIZE {
+        1
+    } else {
+        (mono.len() - FRAME_SIZE) / HOP_SIZE + 1
+    };
+
+    for f in 0..num_frames {
+        let start = f * HOP_SIZE;
+        let frame = &mono[start..(start + FRAME_SIZE).min(mono.len())];
+
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt();
+        let rms_db = 20.0 * rms.max(1e-10).log10();
+        let zcr = frame
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count() as f32
+            / frame.len() as f32;
+
+        rms_values.push(rms_db);
+        zcrs.push(zcr);
+
+        if rms_db < SILENT_FRAME_DB && num_frames > 1 {
+            continue;
+        }
+
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            let s = frame.get(i).copied().unwrap_or(0.0);
+            *slot = Complex::new(s * window[i], 0.0);
+        }
+        fft.process(&mut buffer);
+
+        let power: Vec<f32> = buffer[..FRAME_SIZE / 2 + 1]
+            .iter()
+            .map(|c| c.norm_sqr())
+            .collect();
+        let total_power: f32 = power.iter().sum();
+        if total_power <= 0.0 {
+            continue;
+        }
+
+        // Spectral centroid (pakai magnitude)
+        let magnitudes: Vec<f32> = power.iter().map(|p| p.sqrt()).collect();
+        let mag_sum: f32 = magnitudes.iter().sum();
+        let centroid = magnitudes
+            .iter()
+            .enumerate()
+            .map(|(bin, m)| bin as f32 * bin_hz * m)
+            .sum::<f32>()
+            / mag_sum;
+        centroids.push(centroid);
+
+        // Rolloff: frekuensi tempat 85% energi sudah terkumpul
+        let mut acc = 0.0;
+        let rolloff_bin = power
+            .iter()
+            .position(|p| {
+                acc += p;
+                acc >= ROLLOFF_RATIO * total_power
+            })
+            .unwrap_or(power.len() - 1);
+        rolloffs.push(rolloff_bin as f32 * bin_hz);
+
+        // Flatness: geometric mean / arithmetic mean
+        let log_mean = power.iter().map(|p| (p + 1e-12).ln()).sum::<f32>() / power.len() as f32;
+        flatnesses.push(log_mean.exp() / (total_power / power.len() as f32));
+
+        // MFCC: log mel energy lalu DCT-II
+        let log_mel: Vec<f32> = filterbank
+            .iter()
+            .map(|weights| {
+                let energy: f32 = weights.iter().zip(&power).map(|(w, p)| w * p).sum();
+                (energy + 1e-10).ln()
+            })
+            .collect();
+
+        let mut coeffs = [0.0f32; NUM_MFCC];
+        for (k, coeff) in coeffs.iter_mut().enumerate() {
+            *coeff = log_mel
+                .iter()
+                .enumerate()
+                .map(|(m, e)| e * (PI * k as f32 * (m as f32 + 0.5) / NUM_MEL_BANDS as f32).cos())
+                .sum();
+        }
+        mfccs.push(coeffs);
+    }
+
+    let mut mfcc_mean = Vec::with_capacity(NUM_MFCC);
+    let mut mfcc_std = Vec::with_capacity(NUM_MFCC);
+    for k in 0..NUM_MFCC {
+        let column: Vec<f32> = mfccs.iter().map(|c| c[k]).collect();
+        let (mean, std) = mean_std(&column);
+        mfcc_mean.push(mean);
+        mfcc_std.push(std);
+    }
+
+    let (spectral_centroid, spectral_centroid_std) = mean_std(&centroids);
+    let (zero_crossing_rate, zero_crossing_rate_std) = mean_std(&zcrs);
+    let (rms_db, rms_db_std) = mean_std(&rms_values);
+
+    Some(AudioFeatures {
+        mfcc_mean,
+        mfcc_std,
+        spectral_centroid,
+        spectral_centroid_std,
+        spectral_rolloff: mean_std(&rolloffs).0,
+        spectral_flatness: mean_std(&flatnesses).0,
+        zero_crossing_rate,
+        zero_crossing_rate_std,
+        rms_db,
+        rms_db_std,
+        duration_sec: duration_sec as f32,
+    })
+}
+
+#[tauri::command]
+pub fn generate_missing_features(
+    app: AppHandle,
+    state: State<'_, DbState>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, original_path, filename FROM assets
+             WHERE type = 'audio' AND id NOT IN (SELECT asset_id FROM audio_features)",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,    // id
+                    row.get::<_, String>(1)?, // path
+                    row.get::<_, String>(2)?, // filename
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Semua fitur audio sudah lengkap.".to_string());
+    }
+
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, path, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "feature-progress",
+                ProgressEvent {
+                    name: "Feature".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            update_audio_features(&db_arc, *id, path, filename);
+        });
+
+        let _ = app.emit(
+            "feature-progress",
+            ProgressEvent {
+                name: "Feature".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai ekstraksi fitur audio untuk {} file...",
+        total_files
+    ))
+}
+
+pub fn load_all_features(conn: &rusqlite::Connection) -> Result<Vec<(i64, AudioFeatures)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT asset_id, features FROM audio_features")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(id, json)| serde_json::from_str(&json).ok().map(|f| (id, f)))
+        .collect())
+}
+
+#[tauri::command]
+pub fn find_similar_assets(
+    state: State<'_, DbState>,
+    asset_id: i64,
+    limit: u32,
+) -> Result<Vec<SimilarAsset>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let all = load_all_features(&conn)?;
+    let vectors: Vec<(i64, Vec<f32>)> = all.iter().map(|(id, f)| (*id, f.to_vector())).collect();
+
+    let target = vectors
+        .iter()
+        .find(|(id, _)| *id == asset_id)
+        .map(|(_, v)| v.clone())
+        .ok_or("Fitur audio untuk asset ini belum dihitung")?;
+
+    // Normalisasi z-score per dimensi supaya MFCC dan Hz punya bobot setara
+    let dims = target.len();
+    let mut scale = vec![1.0f32; dims];
+    for (d, s) in scale.iter_mut().enumerate() {
+        let column: Vec<f32> = vectors.iter().map(|(_, v)| v[d]).collect();
+        let (_, std) = mean_std(&column);
+        if std > 1e-6 {
+            *s = 1.0 / std;
+        }
+    }
+
+    let mut distances: Vec<(i64, f32)> = vectors
+        .iter()
+        .filter(|(id, v)| *id != asset_id && v.len() == dims)
+        .map(|(id, v)| {
+            let dist = v
+                .iter()
+                .zip(&target)
+                .zip(&scale)
+                .map(|((a, b), s)| ((a - b) * s).powi(2))
+                .sum::<f32>()
+                .sqrt();
+            (*id, dist)
+        })
+        .collect();
+
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    distances.truncate(limit as usize);
+
+    if distances.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders = vec!["?"; distances.len()].join(", ");
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM assets WHERE id IN ({})",
+            ASSET_COLUMNS, placeholders
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let ids: Vec<i64> = distances.iter().map(|(id, _)| *id).collect();
+    let mut assets: HashMap<i64, _> = stmt
+        .query_map(rusqlite::params_from_iter(ids.iter()), asset_from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter_map(|asset| asset.id.map(|id| (id, asset)))
+        .collect();
+
+    // Pertahankan urutan dari yang paling mirip
+    Ok(distances
+        .into_iter()
+        .filter_map(|(id, distance)| {
+            assets
+                .remove(&id)
+                .map(|asset| SimilarAsset { asset, distance })
+        })
+        .collect())
+}
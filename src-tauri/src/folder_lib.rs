@@ -1,11 +1,15 @@
 use notify::{Event, EventKind, RecursiveMode, Result as NotifyResult, Watcher};
+use rusqlite::OptionalExtension;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, State};
 use tokio::fs;
 use walkdir::WalkDir;
 
-use crate::{models::DbState, utils::get_media_type};
+use crate::{
+    audio_meta_lib::is_temp_path, models::DbState, proxy_lib::remove_proxy,
+    thumb_cache_lib::rekey_if_changed, ucs_lib::apply_ucs_fields, utils::get_media_type,
+};
 
 #[derive(Clone, serde::Serialize)]
 struct ScanProgress {
@@ -225,8 +229,9 @@ fn handle_file_change(
             if let Some(new_path) = event.paths.first() {
                 println!("==> Rename TO detected: {}", new_path.display());
 
-                // Get the old path if available
-                let old_path = rename_from.take();
+                // Get the old path if available. File sementara hasil tulis metadata
+                // (*.editon-tmp -> file asli) bukan rename asset: cukup update file aslinya
+                let old_path = rename_from.take().filter(|old| !is_temp_path(old));
 
                 // Process the new path
                 if new_path.is_file() {
@@ -350,7 +355,34 @@ fn handle_file_change(
     }
 }
 
-fn add_or_update_file_in_db(
+// Import satu file hasil export/konversi tanpa menunggu folder watcher
+pub fn import_single_file(
+    conn: &Arc<Mutex<rusqlite::Connection>>,
+    path: &Path,
+) -> Result<(), String> {
+    let ext_str = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let media_type = get_media_type(&ext_str).ok_or("Bukan file media yang didukung")?;
+    let filename = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let file_size = path.metadata().map(|m| m.len()).unwrap_or(0);
+
+    add_or_update_file_in_db(
+        conn,
+        &filename,
+        &ext_str,
+        &path.to_string_lossy(),
+        &media_type,
+        file_size,
+    )
+}
+
+pub fn add_or_update_file_in_db(
     conn: &Arc<Mutex<rusqlite::Connection>>,
     filename: &str,
     ext: &str,
@@ -370,6 +402,7 @@ fn add_or_update_file_in_db(
 
     match result {
         Ok(_) => {
+            apply_ucs_fields(&tx, path, filename)?;
             tx.commit().map_err(|e| e.to_string())?;
             Ok(())
         }
@@ -382,6 +415,8 @@ fn add_or_update_file_in_db(
                 rusqlite::params![size as i64, path],
             )
             .map_err(|e| e.to_string())?;
+            rekey_if_changed(&tx, path)?;
+            apply_ucs_fields(&tx, path, filename)?;
             tx.commit().map_err(|e| e.to_string())?;
             Ok(())
         }
@@ -391,6 +426,20 @@ fn add_or_update_file_in_db(
 
 fn remove_file_from_db(conn: &Arc<Mutex<rusqlite::Connection>>, path: &str) -> Result<(), String> {
     let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    // Baris proxy ikut terhapus lewat cascade, file-nya harus dibuang di sini
+    let asset_id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM assets WHERE original_path = ?1",
+            [path],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(asset_id) = asset_id {
+        remove_proxy(&conn, asset_id)?;
+    }
+
     conn.execute(
         "DELETE FROM assets WHERE original_path = ?1",
         rusqlite::params![path],
@@ -426,6 +475,7 @@ fn replace_file_in_db(
             rusqlite::params![filename, ext, media_type, size as i64, path],
         )
         .map_err(|e| e.to_string())?;
+        rekey_if_changed(&conn, path)?;
         println!("✓ File replaced in DB: {}", path);
     } else {
         // File doesn't exist, insert as new
@@ -438,6 +488,8 @@ fn replace_file_in_db(
         println!("✓ File inserted in DB: {}", path);
     }
 
+    apply_ucs_fields(&conn, path, filename)?;
+
     Ok(())
 }
 
@@ -481,6 +533,9 @@ fn handle_rename_in_db(
         println!("✓ File inserted in DB (old path not found): {}", new_path);
     }
 
+    // Nama baru bisa saja mengubah (atau menghapus) kategori UCS
+    apply_ucs_fields(&conn, new_path, new_filename)?;
+
     Ok(())
 }
 
@@ -511,6 +566,8 @@ fn save_batch(
                 0.0,
             ])
             .map_err(|e| e.to_string())?;
+
+            apply_ucs_fields(&tx, path, filename)?;
         }
     }
     tx.commit().map_err(|e| e.to_string())?;
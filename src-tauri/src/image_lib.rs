@@ -1,19 +1,29 @@
-use fast_image_resize::{images::Image, PixelType, ResizeAlg, ResizeOptions, Resizer};
-use image::codecs::webp::WebPEncoder;
-use image::ExtendedColorType;
-use image::ImageEncoder;
+use fast_image_resize::{images::Image, FilterType, PixelType, ResizeAlg, ResizeOptions, Resizer};
 use image::ImageReader;
+use image::{DynamicImage, ImageDecoder};
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformOptions};
 use rayon::prelude::*;
 use std::fs::File;
 use std::io::BufReader;
-use std::io::Cursor;
 use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tauri::Emitter;
 use tauri::Manager;
 
+use crate::anim_lib::{animated_preview_path, build_animated_preview, is_animation_candidate};
+use crate::color_lib::{dominant_colors, replace_palette, PALETTE_SIZE};
+use crate::image_meta_lib::{exif_orientation, image_search_text, read_image_metadata};
 use crate::models::ApiResponse;
 use crate::models::ProgressEvent;
+use crate::models::ThumbnailSettings;
+use crate::pro_image_lib::{
+    decode_exr, decode_psd, decode_raw_preview, image_info, is_exr, is_heif, is_psd, is_raw,
+    ImageInfo,
+};
+use crate::svg_lib::{is_svg, rasterize_svg, svg_dimensions};
+use crate::thumb_cache_lib::{collect_garbage, content_key};
+use crate::video_lib::{extract_poster_frame, extract_video_frame, probe_and_store};
 use crate::AssetMetadata;
 use crate::DbState;
 
@@ -25,37 +35,217 @@ pub fn cancel_scan(state: tauri::State<'_, DbState>) -> Result<String, String> {
 }
 
 pub fn get_image_metadata(path: &str, ext: &str) -> AssetMetadata {
-    if ext == "svg" {
-        return AssetMetadata::None;
-    }
+    let info = if is_svg(ext) {
+        svg_dimensions(path).map(|(width, height)| ImageInfo {
+            width,
+            height,
+            ..Default::default()
+        })
+    } else {
+        image_info(path, ext)
+    };
 
-    match image::image_dimensions(path) {
-        Ok((w, h)) => AssetMetadata::Image {
-            width: w,
-            height: h,
-            format: ext.to_string(),
-        },
+    match info {
+        Ok(info) => {
+            let mut meta = read_image_metadata(path, info.width, info.height, ext);
+            meta.bit_depth = info.bit_depth;
+            meta.layer_count = info.layer_count;
+            meta.page_count = info.page_count;
+            AssetMetadata::Image(Box::new(meta))
+        }
         Err(_) => AssetMetadata::None,
     }
 }
 
-pub fn generate_thumbnail_buffer(path: &str, target_width: u32) -> Result<Vec<u8>, String> {
+// Pilih decoder sesuai format. SVG dirender seukuran thumbnail terbesar,
+// HEIC/AVIF lewat ffmpeg, RAW pakai preview JPEG embedded.
+fn decode_for_thumbnail(
+    app: &tauri::AppHandle,
+    path: &str,
+    ext: &str,
+    settings: &ThumbnailSettings,
+) -> Result<image::RgbaImage, String> {
+    if is_svg(ext) {
+        let max_size = settings.sizes.iter().copied().max();
+        rasterize_svg(path, max_size.unwrap_or(GRID_THUMBNAIL_SIZE))
+    } else if is_psd(ext) {
+        decode_psd(path)
+    } else if is_raw(ext) {
+        decode_raw_preview(path, exif_orientation(path))
+    } else if is_heif(ext) {
+        extract_video_frame(app, Path::new(path), 0.0, None)
+    } else if is_exr(ext) {
+        decode_exr(path)
+    } else {
+        decode_display_rgba(path)
+    }
+}
+
+// Decode gambar ke RGBA8 sesuai tampilan: orientasi EXIF diterapkan dan warna dikonversi ke sRGB
+pub fn decode_display_rgba(path: &str) -> Result<image::RgbaImage, String> {
     // 1. Buka File (Gunakan BufReader untuk sedikit optimasi I/O)
     let file = File::open(path).map_err(|e| e.to_string())?;
     let reader = BufReader::new(file);
 
-    // 2. Decode ke RGBA8
-    let img = ImageReader::new(reader)
+    // 2. Ambil orientasi + ICC profile dari decoder sebelum decode
+    let mut decoder = ImageReader::new(reader)
         .with_guessed_format()
         .map_err(|e| e.to_string())?
-        .decode()
+        .into_decoder()
+        .map_err(|e| e.to_string())?;
+    let orientation = decoder
+        .orientation()
+        .unwrap_or(image::metadata::Orientation::NoTransforms);
+    let icc_profile = decoder.icc_profile().ok().flatten();
+
+    let mut img = DynamicImage::from_decoder(decoder).map_err(|e| e.to_string())?;
+    img.apply_orientation(orientation);
+
+    // 3. Decode ke RGBA8
+    let mut rgba = img.to_rgba8();
+    if let Some(icc) = icc_profile {
+        // Profile rusak/tidak didukung: pakai warna apa adanya
+        if let Err(e) = convert_to_srgb(&mut rgba, &icc) {
+            println!("ICC profile {} diabaikan: {}", path, e);
+        }
+    }
+
+    Ok(rgba)
+}
+
+// Konversi buffer RGBA8 dari ICC profile embedded (Display P3, Adobe RGB, ...) ke sRGB
+fn convert_to_srgb(img: &mut image::RgbaImage, icc: &[u8]) -> Result<(), String> {
+    let source = ColorProfile::new_from_slice(icc).map_err(|e| e.to_string())?;
+    // Profile CMYK/grayscale tidak berlaku untuk buffer RGBA hasil decode
+    if source.color_space != DataColorSpace::Rgb {
+        return Ok(());
+    }
+
+    let transform = source
+        .create_transform_8bit(
+            Layout::Rgba,
+            &ColorProfile::new_srgb(),
+            Layout::Rgba,
+            TransformOptions::default(),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let src = img.as_raw().clone();
+    transform.transform(&src, img).map_err(|e| e.to_string())
+}
+
+// Lebar thumbnail default: grid kecil, grid besar, preview
+const DEFAULT_THUMBNAIL_SIZES: [u32; 3] = [128, 256, 512];
+const DEFAULT_THUMBNAIL_QUALITY: f32 = 80.0;
+// Ukuran yang disimpan di kolom thumbnail_path (dipakai grid)
+const GRID_THUMBNAIL_SIZE: u32 = 256;
+const MIN_THUMBNAIL_SIZE: u32 = 16;
+const BLURHASH_SAMPLE_WIDTH: u32 = 32;
+const MAX_THUMBNAIL_SIZE: u32 = 2048;
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            sizes: DEFAULT_THUMBNAIL_SIZES.to_vec(),
+            quality: DEFAULT_THUMBNAIL_QUALITY,
+            cache_dir: None,
+            max_cache_mb: None,
+        }
+    }
+}
+
+pub fn default_thumbnails_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("thumbnails"))
+}
+
+// Folder cache thumbnail: cache_dir dari setting (bisa di drive lain) atau default di app data
+pub fn thumbnails_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = match load_thumbnail_settings(app).cache_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => default_thumbnails_dir(app)?,
+    };
+
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
         .map_err(|e| e.to_string())?
-        .to_rgba8();
+        .join("thumbnail_settings.json"))
+}
+
+// File setting belum ada/rusak = pakai default
+pub fn load_thumbnail_settings(app: &tauri::AppHandle) -> ThumbnailSettings {
+    settings_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_thumbnail_settings(
+    app: &tauri::AppHandle,
+    settings: &ThumbnailSettings,
+) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+// Rapikan input user: ukuran unik & terurut dalam batas wajar, quality 0-100
+fn normalize_settings(mut settings: ThumbnailSettings) -> Result<ThumbnailSettings, String> {
+    settings
+        .sizes
+        .retain(|s| (MIN_THUMBNAIL_SIZE..=MAX_THUMBNAIL_SIZE).contains(s));
+    settings.sizes.sort_unstable();
+    settings.sizes.dedup();
+
+    if settings.sizes.is_empty() {
+        return Err(format!(
+            "Ukuran thumbnail harus antara {} dan {} px",
+            MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE
+        ));
+    }
+    if !(0.0..=100.0).contains(&settings.quality) {
+        return Err("Quality harus antara 0 dan 100".to_string());
+    }
+    Ok(settings)
+}
+
+// Ukuran terkecil yang >= wanted; kalau tidak ada, ukuran terbesar
+fn pick_size(sizes: &[u32], wanted: u32) -> u32 {
+    sizes
+        .iter()
+        .copied()
+        .filter(|s| *s >= wanted)
+        .min()
+        .or_else(|| sizes.iter().copied().max())
+        .unwrap_or(GRID_THUMBNAIL_SIZE)
+}
+
+// Nama file pakai key isi file (lihat thumb_cache_lib::content_key), bukan id asset
+fn thumbnail_file(dir: &Path, key: &str, size: u32) -> PathBuf {
+    dir.join(format!("{}_{}.webp", key, size))
+}
 
+// Resize gambar RGBA ke lebar target (Lanczos3: tajam tanpa aliasing, alpha ikut
+// diperhitungkan). Gambar yang lebih kecil dari target tidak di-upscale.
+fn resize_rgba(img: &image::RgbaImage, target_width: u32) -> Result<Image<'static>, String> {
     let width = NonZeroU32::new(img.width()).ok_or("Width 0")?;
     let height = NonZeroU32::new(img.height()).ok_or("Height 0")?;
 
-    // 3. Setup Fast Image Resize
     let src_view = fast_image_resize::images::ImageRef::new(
         width.get(),
         height.get(),
@@ -64,38 +254,253 @@ pub fn generate_thumbnail_buffer(path: &str, target_width: u32) -> Result<Vec<u8
     )
     .map_err(|e| e.to_string())?;
 
+    let target_width = target_width.min(width.get());
     let aspect_ratio = width.get() as f32 / height.get() as f32;
-    let target_height = (target_width as f32 / aspect_ratio) as u32;
+    let target_height = ((target_width as f32 / aspect_ratio).round() as u32).max(1);
     let dst_width = NonZeroU32::new(target_width).ok_or("Target width 0")?;
-    let dst_height = NonZeroU32::new(target_height).ok_or("Target height 0")?;
 
-    let mut dst_image = Image::new(dst_width.get(), dst_height.get(), PixelType::U8x4);
+    let mut dst_image = Image::new(dst_width.get(), target_height, PixelType::U8x4);
 
-    // 4. Resize (Nearest Neighbor untuk kecepatan maksimal)
     let mut resizer = Resizer::new();
     resizer
         .resize(
             &src_view,
             &mut dst_image,
-            &ResizeOptions::new().resize_alg(ResizeAlg::Nearest),
+            &ResizeOptions::new().resize_alg(ResizeAlg::Convolution(FilterType::Lanczos3)),
         )
         .map_err(|e| e.to_string())?;
 
-    // 5. Encode ke WebP (Raw Bytes)
-    let mut buffer = Cursor::new(Vec::new());
-    let encoder = WebPEncoder::new_lossless(&mut buffer); // Lossy
+    Ok(dst_image)
+}
+
+// Resize lalu encode ke WebP lossy (dipakai juga untuk frame video)
+pub fn encode_thumbnail(
+    img: &image::RgbaImage,
+    target_width: u32,
+    quality: f32,
+) -> Result<Vec<u8>, String> {
+    let resized = resize_rgba(img, target_width)?;
+    encode_webp(resized.buffer(), resized.width(), resized.height(), quality)
+}
+
+// BlurHash dari versi kecil gambar (encode BlurHash O(pixel x komponen), jadi jangan
+// dari resolusi penuh). Komponen mengikuti orientasi gambar.
+pub fn compute_blurhash(img: &image::RgbaImage) -> Option<String> {
+    let small = resize_rgba(img, BLURHASH_SAMPLE_WIDTH).ok()?;
+    let (x, y) = if small.width() >= small.height() {
+        (4, 3)
+    } else {
+        (3, 4)
+    };
+    blurhash::encode(x, y, small.width(), small.height(), small.buffer()).ok()
+}
+
+// Encode buffer RGBA8 ke WebP lossy (libwebp), quality 0-100
+pub fn encode_webp(rgba: &[u8], width: u32, height: u32, quality: f32) -> Result<Vec<u8>, String> {
+    let webp = libwebp::WebPEncodeRGBA(rgba, width, height, width * 4, quality)
+        .map_err(|e| e.to_string())?;
+
+    Ok(webp.to_vec())
+}
+
+// Tulis semua ukuran thumbnail; return path ukuran grid untuk kolom thumbnail_path
+pub fn write_thumbnails(
+    dir: &Path,
+    key: &str,
+    img: &image::RgbaImage,
+    settings: &ThumbnailSettings,
+) -> Result<PathBuf, String> {
+    for size in &settings.sizes {
+        let blob = encode_thumbnail(img, *size, settings.quality)?;
+        std::fs::write(thumbnail_file(dir, key, *size), blob).map_err(|e| e.to_string())?;
+    }
+
+    Ok(thumbnail_file(
+        dir,
+        key,
+        pick_size(&settings.sizes, GRID_THUMBNAIL_SIZE),
+    ))
+}
+
+// Hapus semua file thumbnail asset (semua ukuran + preview animasi). File format lama
+// berbasis id dibersihkan gc_thumbnail_cache. thumbnail_path di luar folder thumbnail
+// (mis. SVG asli) tidak disentuh.
+fn remove_thumbnails(dir: &Path, key: Option<&str>, thumbnail_path: Option<&str>) {
+    if let (Some(key), Ok(entries)) = (key, std::fs::read_dir(dir)) {
+        let prefix = format!("{}_", key);
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let size_part = name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".webp"));
+            // {key}_{size}.webp dan preview animasi {key}_anim.webp
+            if size_part.is_some_and(|s| s == "anim" || s.parse::<u32>().is_ok()) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    if let Some(path) = thumbnail_path.map(Path::new) {
+        if path.starts_with(dir) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_thumbnail_settings(app: tauri::AppHandle) -> Result<ThumbnailSettings, String> {
+    Ok(load_thumbnail_settings(&app))
+}
 
-    encoder
-        .write_image(
-            dst_image.buffer(),
-            target_width,
-            target_height,
-            ExtendedColorType::Rgba8,
+// Buat ulang file cache satu asset yang sudah di-evict (thumb_cache_lib::evict_to_limit).
+// Dipanggil saat thumbnail diminta, bukan lewat generate_missing_thumbnails, supaya
+// tidak terjadi siklus evict -> generate ulang -> evict.
+fn restore_evicted_thumbnail(
+    app: &tauri::AppHandle,
+    path: &str,
+    extension: &str,
+    asset_type: &str,
+    duration: f64,
+    key: &str,
+) -> Result<PathBuf, String> {
+    let dir = thumbnails_dir(app)?;
+    let settings = load_thumbnail_settings(app);
+
+    let img = if asset_type == "video" {
+        extract_poster_frame(app, Path::new(path), duration)?
+    } else {
+        decode_for_thumbnail(app, path, extension, &settings)?
+    };
+
+    if is_animation_candidate(extension) {
+        let width = pick_size(&settings.sizes, GRID_THUMBNAIL_SIZE);
+        if let Ok(Some(anim)) = build_animated_preview(path, extension, width, settings.quality) {
+            let _ = std::fs::write(animated_preview_path(&dir, key), &anim.webp);
+        }
+    }
+
+    write_thumbnails(&dir, key, &img, &settings)
+}
+
+// Path thumbnail paling pas untuk lebar tampilan `size` (mis. 512 untuk tampilan besar).
+// Thumbnail yang sudah di-evict dari cache dibuat ulang di sini.
+#[tauri::command]
+pub async fn get_thumbnail_path(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, DbState>,
+    asset_id: i64,
+    size: u32,
+) -> Result<Option<String>, String> {
+    let (thumbnail_path, key, path, extension, asset_type, duration): (
+        Option<String>,
+        Option<String>,
+        String,
+        String,
+        String,
+        f64,
+    ) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT thumbnail_path, thumbnail_key, original_path, extension, type,
+                    COALESCE(duration_sec, 0)
+             FROM assets WHERE id = ?1",
+            [asset_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Asset tidak ditemukan: {}", e))?
+    };
+
+    let (Some(thumbnail_path), Some(key)) = (thumbnail_path.clone(), key) else {
+        return Ok(thumbnail_path);
+    };
+
+    let settings = load_thumbnail_settings(&app);
+    let sized = thumbnail_file(
+        &thumbnails_dir(&app)?,
+        &key,
+        pick_size(&settings.sizes, size),
+    );
+
+    if sized.exists() {
+        return Ok(Some(sized.to_string_lossy().to_string()));
+    }
+    if Path::new(&thumbnail_path).exists() {
+        return Ok(Some(thumbnail_path));
+    }
+
+    let restore_app = app.clone();
+    let restored = tauri::async_runtime::spawn_blocking(move || {
+        restore_evicted_thumbnail(&restore_app, &path, &extension, &asset_type, duration, &key)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let restored = restored.to_string_lossy().to_string();
+    if restored != thumbnail_path {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE assets SET thumbnail_path = ?1 WHERE id = ?2",
+            rusqlite::params![restored, asset_id],
         )
         .map_err(|e| e.to_string())?;
+    }
 
-    // Return Vec<u8> (BLOB siap simpan ke SQLite)
-    Ok(buffer.into_inner())
+    if sized.exists() {
+        return Ok(Some(sized.to_string_lossy().to_string()));
+    }
+    Ok(Some(restored))
+}
+
+// Simpan setting baru (kalau ada), hapus semua thumbnail lama lalu generate ulang.
+// Lokasi & batas cache tidak diubah di sini (move_thumbnail_cache / set_thumbnail_cache_limit).
+#[tauri::command]
+pub fn regenerate_thumbnails(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, DbState>,
+    settings: Option<ThumbnailSettings>,
+) -> Result<ApiResponse, String> {
+    if let Some(mut settings) = settings {
+        let current = load_thumbnail_settings(&app);
+        settings.cache_dir = current.cache_dir;
+        settings.max_cache_mb = current.max_cache_mb;
+        save_thumbnail_settings(&app, &normalize_settings(settings)?)?;
+    }
+
+    let dir = thumbnails_dir(&app)?;
+    {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT thumbnail_key, thumbnail_path FROM assets WHERE type IN ('image', 'video')",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows: Vec<(Option<String>, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        for (key, thumbnail_path) in rows {
+            remove_thumbnails(&dir, key.as_deref(), thumbnail_path.as_deref());
+        }
+
+        conn.execute(
+            "UPDATE assets SET thumbnail_path = NULL WHERE type IN ('image', 'video')",
+            [],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    generate_missing_thumbnails(app, state)
 }
 
 #[tauri::command]
@@ -109,21 +514,16 @@ pub fn generate_missing_thumbnails(
     let cancel_flag = state.cancel_scan.clone();
 
     // 1. Tentukan lokasi folder thumbnail di AppData
-    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let thumbnails_dir = app_data_dir.join("thumbnails");
-
-    // Pastikan folder thumbnails sudah ada
-    if !thumbnails_dir.exists() {
-        std::fs::create_dir_all(&thumbnails_dir).map_err(|e| e.to_string())?;
-    }
-    // 2. Ambil daftar file (khusus image) yang thumbnail_path-nya masih kosong/NULL
-    let to_process: Vec<(i64, String, String, String)> = {
+    let thumbnails_dir = thumbnails_dir(&app)?;
+    let settings = load_thumbnail_settings(&app);
+    // 2. Ambil daftar file (image & video) yang thumbnail_path-nya masih kosong/NULL
+    let to_process: Vec<(i64, String, String, String, String, f64)> = {
         let conn = db_arc.lock().map_err(|e| e.to_string())?;
         let mut stmt = conn
             .prepare(
-                "SELECT id, original_path, filename, extension FROM assets 
-             WHERE (thumbnail_path IS NULL)
-             AND type = 'image'", // Untuk saat ini kita fokus ke image
+                "SELECT id, original_path, filename, extension, type, COALESCE(duration_sec, 0) FROM assets 
+             WHERE (thumbnail_path IS NULL OR thumbnail_path = original_path)
+             AND type IN ('image', 'video')",
             )
             .map_err(|e| e.to_string())?;
 
@@ -134,6 +534,8 @@ pub fn generate_missing_thumbnails(
                     row.get::<_, String>(1)?, // path
                     row.get::<_, String>(2)?, // filename
                     row.get::<_, String>(3)?, // extension
+                    row.get::<_, String>(4)?, // type
+                    row.get::<_, f64>(5)?,    // duration
                 ))
             })
             .map_err(|e| e.to_string())?;
@@ -154,20 +556,19 @@ pub fn generate_missing_thumbnails(
     std::thread::spawn(move || {
         to_process
             .par_iter()
-            .for_each(|(id, path, filename, extension)| {
+            .for_each(|(id, path, filename, extension, asset_type, duration)| {
                 // Check cancel flag FIRST before processing
                 if cancel_flag.load(Ordering::SeqCst) {
                     return;
                 };
 
-                let metadata = get_image_metadata(path, extension);
-                let metadata_json = serde_json::to_string(&metadata).unwrap_or("{}".to_string());
+                let is_video = asset_type == "video";
                 let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
 
                 let _ = app.emit(
                     "thumbnail-progress",
                     ProgressEvent {
-                        name: "Image".to_string(),
+                        name: if is_video { "Video" } else { "Image" }.to_string(),
                         current,
                         total: total_files,
                         filename: filename.clone(),
@@ -175,38 +576,135 @@ pub fn generate_missing_thumbnails(
                     },
                 );
 
-                if extension.to_lowercase() == "svg" {
-                    if let Ok(conn) = db_arc.lock() {
-                        let _ = conn.execute(
-                            "UPDATE assets SET thumbnail_path = ?1 WHERE id = ?2",
-                            rusqlite::params![path, id],
-                        );
+                // File tidak bisa dibaca: tidak ada yang bisa di-thumbnail
+                let key = match content_key(path) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        println!("Gagal membaca {}: {}", filename, e);
+                        return;
+                    }
+                };
+
+                if is_video {
+                    // Video baru belum punya durasi: probe dulu (sekalian simpan metadatanya)
+                    let duration = if *duration > 0.0 {
+                        *duration
+                    } else {
+                        probe_and_store(&app, &db_arc, *id, path)
+                            .map(|(_, duration)| duration)
+                            .unwrap_or(0.0)
                     };
-                    return;
-                }
 
-                match generate_thumbnail_buffer(path, 200) {
-                    Ok(blob) => {
-                        // Simpan blob ke file system
-                        let thumb_filename = format!("{}.webp", id);
-                        let thumb_path = thumbnails_dir.join(&thumb_filename);
-                        let thumb_path_str = thumb_path.to_string_lossy().to_string();
+                    let mut blurhash = None;
+                    let thumb_path = extract_poster_frame(&app, Path::new(path), duration)
+                        .and_then(|frame| {
+                            blurhash = compute_blurhash(&frame);
+                            write_thumbnails(&thumbnails_dir, &key, &frame, &settings)
+                        });
 
-                        if let Ok(_) = std::fs::write(&thumb_path, &blob) {
-                            // Update database: simpan path-nya dan hapus blob untuk menghemat space DB
+                    match thumb_path {
+                        Ok(thumb_path) => {
                             if let Ok(conn) = db_arc.lock() {
                                 let _ = conn.execute(
-                                    "UPDATE assets SET thumbnail_path = ?1, metadata = ?2 WHERE id = ?3",
-                                    rusqlite::params![thumb_path_str, metadata_json, id],
+                                    "UPDATE assets SET thumbnail_path = ?1, blurhash = ?2, thumbnail_key = ?3
+                                     WHERE id = ?4",
+                                    rusqlite::params![
+                                        thumb_path.to_string_lossy().to_string(),
+                                        blurhash,
+                                        key,
+                                        id
+                                    ],
                                 );
                             }
                         }
+                        Err(e) => {
+                            println!("Gagal generate thumbnail video untuk {}: {}", filename, e);
+                        }
+                    }
+                    return;
+                }
+
+                let mut metadata = get_image_metadata(path, extension);
+
+                // GIF/WebP/APNG animasi: preview hover + jumlah frame, loop dan durasi
+                let mut anim_duration = None;
+                if is_animation_candidate(extension) {
+                    let width = pick_size(&settings.sizes, GRID_THUMBNAIL_SIZE);
+                    match build_animated_preview(path, extension, width, settings.quality) {
+                        Ok(Some(anim)) => {
+                            let _ = std::fs::write(
+                                animated_preview_path(&thumbnails_dir, &key),
+                                &anim.webp,
+                            );
+                            if let AssetMetadata::Image(image) = &mut metadata {
+                                image.frame_count = Some(anim.frame_count);
+                                image.loop_count = Some(anim.loop_count);
+                                image.duration_sec = Some(anim.duration_sec);
+                            }
+                            anim_duration = Some(anim.duration_sec);
+                        }
+                        Ok(None) => {}
+                        Err(e) => println!("Gagal membuat preview animasi {}: {}", filename, e),
+                    }
+                }
+
+                let metadata_json = serde_json::to_string(&metadata).unwrap_or("{}".to_string());
+                let (search_text, rating) = match &metadata {
+                    AssetMetadata::Image(image) => (image_search_text(image), image.rating),
+                    _ => (String::new(), None),
+                };
+
+                let decoded = decode_for_thumbnail(&app, path, extension, &settings);
+
+                // Palette dihitung dari buffer yang sama dengan thumbnail
+                let mut palette = Vec::new();
+                let mut blurhash = None;
+                let thumb_path = decoded.and_then(|img| {
+                    palette = dominant_colors(&img, PALETTE_SIZE);
+                    blurhash = compute_blurhash(&img);
+                    write_thumbnails(&thumbnails_dir, &key, &img, &settings)
+                });
+
+                match thumb_path {
+                    Ok(thumb_path) => {
+                        let thumb_path_str = thumb_path.to_string_lossy().to_string();
+
+                        // Update database: simpan path-nya dan hapus blob untuk menghemat space DB
+                        if let Ok(conn) = db_arc.lock() {
+                            // Rating XMP hanya mengisi rating yang belum di-set user
+                            let _ = conn.execute(
+                                "UPDATE assets SET thumbnail_path = ?1, metadata = ?2, search_text = ?3,
+                                        rating = COALESCE(rating, ?4),
+                                        duration_sec = COALESCE(?5, duration_sec), blurhash = ?6,
+                                        thumbnail_key = ?7
+                                 WHERE id = ?8",
+                                rusqlite::params![
+                                    thumb_path_str,
+                                    metadata_json,
+                                    search_text,
+                                    rating,
+                                    anim_duration,
+                                    blurhash,
+                                    key,
+                                    id
+                                ],
+                            );
+                            let _ = replace_palette(&conn, *id, &palette);
+                        }
                     }
                     Err(e) => {
                         println!("Gagal generate thumbnail untuk {}: {}", filename, e);
                     }
                 }
             });
+
+        // Batas ukuran cache (kalau di-set) langsung diterapkan
+        if settings.max_cache_mb.is_some() {
+            if let Err(e) = collect_garbage(&thumbnails_dir, &db_arc, settings.max_cache_mb) {
+                println!("Gagal membersihkan cache thumbnail: {}", e);
+            }
+        }
+
         // D. Emit selesai
         let _ = app.emit(
             "thumbnail-progress",
@@ -222,7 +720,7 @@ pub fn generate_missing_thumbnails(
 
     Ok(ApiResponse {
         message: format!(
-            "Memulai prosess generate thumbnail untuk {} gambar",
+            "Memulai prosess generate thumbnail untuk {} gambar/video",
             total_files
         ),
         status: "Processing".to_string(),
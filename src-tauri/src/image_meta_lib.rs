@@ -0,0 +1,306 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+};
+
+use exif::{In, Tag, Value};
+
+use crate::models::ImageMetadata;
+
+// XMP/IPTC biasanya ada di awal file; batasi baca supaya TIFF/PSD raksasa tidak dibaca utuh
+const MAX_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+
+fn exif_string(exif: &exif::Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    match &field.value {
+        Value::Ascii(values) => values
+            .iter()
+            .map(|v| String::from_utf8_lossy(v).trim().to_string())
+            .find(|v| !v.is_empty()),
+        _ => None,
+    }
+}
+
+fn exif_rational(exif: &exif::Exif, tag: Tag, index: usize) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) => values.get(index).map(|r| r.to_f64()),
+        Value::SRational(values) => values.get(index).map(|r| r.to_f64()),
+        _ => None,
+    }
+    .filter(|v| v.is_finite())
+}
+
+fn exif_uint(exif: &exif::Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+// Orientasi EXIF (1-8) saja, untuk format yang di-decode tanpa crate image (mis. preview RAW)
+pub fn exif_orientation(path: &str) -> Option<u32> {
+    let file = File::open(path).ok()?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .ok()?;
+    exif_uint(&exif, Tag::Orientation)
+}
+
+// Derajat/menit/detik + ref (N/S/E/W) -> derajat desimal
+fn exif_gps(exif: &exif::Exif, tag: Tag, ref_tag: Tag, negative_ref: &str) -> Option<f64> {
+    let deg = exif_rational(exif, tag, 0)?;
+    let min = exif_rational(exif, tag, 1).unwrap_or(0.0);
+    let sec = exif_rational(exif, tag, 2).unwrap_or(0.0);
+    let value = deg + min / 60.0 + sec / 3600.0;
+
+    match exif_string(exif, ref_tag) {
+        Some(r) if r.eq_ignore_ascii_case(negative_ref) => Some(-value),
+        _ => Some(value),
+    }
+}
+
+fn format_shutter(seconds: f64) -> String {
+    if seconds > 0.0 && seconds < 1.0 {
+        format!("1/{}", (1.0 / seconds).round())
+    } else {
+        format!("{}s", (seconds * 10.0).round() / 10.0)
+    }
+}
+
+fn apply_exif(meta: &mut ImageMetadata, path: &str) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let exif = exif::Reader::new()
+        .read_from_container(&mut BufReader::new(file))
+        .map_err(|e| e.to_string())?;
+
+    meta.camera_make = exif_string(&exif, Tag::Make);
+    meta.camera_model = exif_string(&exif, Tag::Model);
+    meta.lens = exif_string(&exif, Tag::LensModel);
+    meta.iso = exif_uint(&exif, Tag::PhotographicSensitivity);
+    meta.shutter_speed = exif_rational(&exif, Tag::ExposureTime, 0).map(format_shutter);
+    meta.aperture = exif_rational(&exif, Tag::FNumber, 0).map(|v| v as f32);
+    meta.focal_length_mm = exif_rational(&exif, Tag::FocalLength, 0).map(|v| v as f32);
+    meta.orientation = exif_uint(&exif, Tag::Orientation);
+    meta.copyright = exif_string(&exif, Tag::Copyright);
+    meta.artist = exif_string(&exif, Tag::Artist);
+    meta.description = exif_string(&exif, Tag::ImageDescription);
+
+    // "2024:01:02 03:04:05" -> "2024-01-02 03:04:05"
+    meta.capture_date = exif_string(&exif, Tag::DateTimeOriginal)
+        .or_else(|| exif_string(&exif, Tag::DateTime))
+        .map(|d| d.replacen(':', "-", 2));
+
+    meta.gps_latitude = exif_gps(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S");
+    meta.gps_longitude = exif_gps(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W");
+    meta.gps_altitude = exif_rational(&exif, Tag::GPSAltitude, 0).map(|alt| {
+        // GPSAltitudeRef 1 = di bawah permukaan laut
+        if exif_uint(&exif, Tag::GPSAltitudeRef) == Some(1) {
+            -alt
+        } else {
+            alt
+        }
+    });
+
+    Ok(())
+}
+
+// Dataset IPTC-IIM record 2 dari resource Photoshop 0x0404 (segmen APP13 JPEG).
+// Return (dataset, value).
+fn read_iptc(bytes: &[u8]) -> Vec<(u8, String)> {
+    let mut datasets = Vec::new();
+
+    let Some(start) = find(bytes, b"Photoshop 3.0\0") else {
+        return datasets;
+    };
+    let mut pos = start + 14;
+
+    // Resource block: "8BIM" id(2) nama(pascal, genap) size(4) data(genap)
+    while pos + 12 <= bytes.len() && &bytes[pos..pos + 4] == b"8BIM" {
+        let id = u16::from_be_bytes([bytes[pos + 4], bytes[pos + 5]]);
+        let name_len = bytes[pos + 6] as usize;
+        let name_total = (name_len + 2) & !1;
+        let size_pos = pos + 6 + name_total;
+        if size_pos + 4 > bytes.len() {
+            break;
+        }
+        let size = u32::from_be_bytes([
+            bytes[size_pos],
+            bytes[size_pos + 1],
+            bytes[size_pos + 2],
+            bytes[size_pos + 3],
+        ]) as usize;
+        let data_start = size_pos + 4;
+        let data_end = (data_start + size).min(bytes.len());
+
+        if id == 0x0404 {
+            let data = &bytes[data_start..data_end];
+            let mut i = 0;
+            while i + 5 <= data.len() && data[i] == 0x1C {
+                let (record, dataset) = (data[i + 1], data[i + 2]);
+                let len = u16::from_be_bytes([data[i + 3], data[i + 4]]) as usize;
+                // Extended dataset (bit tertinggi) tidak dipakai untuk field teks
+                if len & 0x8000 != 0 || i + 5 + len > data.len() {
+                    break;
+                }
+                if record == 2 {
+                    let value = String::from_utf8_lossy(&data[i + 5..i + 5 + len])
+                        .trim()
+                        .to_string();
+                    if !value.is_empty() {
+                        datasets.push((dataset, value));
+                    }
+                }
+                i += 5 + len;
+            }
+            break;
+        }
+
+        pos = data_start + ((size + 1) & !1);
+    }
+
+    datasets
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn decode_xml_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+// Nilai properti XMP, bentuk atribut (xmp:Rating="3") atau elemen (<xmp:Rating>3</xmp:Rating>)
+fn xmp_value(xmp: &str, name: &str) -> Option<String> {
+    let attr = format!("{}=\"", name);
+    if let Some(start) = xmp.find(&attr) {
+        let rest = &xmp[start + attr.len()..];
+        return rest.find('"').map(|end| decode_xml_entities(&rest[..end]));
+    }
+
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = xmp.find(&open)? + open.len();
+    let end = xmp[start..].find(&close)? + start;
+    Some(decode_xml_entities(&xmp[start..end]))
+}
+
+// Isi <rdf:li> di dalam properti bertipe Bag/Seq/Alt (dc:subject, dc:rights, ...)
+fn xmp_list(xmp: &str, name: &str) -> Vec<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let Some(start) = xmp.find(&open).map(|s| s + open.len()) else {
+        return Vec::new();
+    };
+    let Some(end) = xmp[start..].find(&close).map(|e| e + start) else {
+        return Vec::new();
+    };
+
+    xmp[start..end]
+        .split("<rdf:li")
+        .skip(1)
+        .filter_map(|item| {
+            let content = &item[item.find('>')? + 1..];
+            let value = decode_xml_entities(&content[..content.find("</rdf:li>")?]);
+            (!value.is_empty()).then_some(value)
+        })
+        .collect()
+}
+
+fn apply_xmp(meta: &mut ImageMetadata, xmp: &str) {
+    if let Some(rating) = xmp_value(xmp, "xmp:Rating").and_then(|r| r.parse::<f32>().ok()) {
+        meta.rating = Some(rating.round() as i32);
+    }
+
+    for keyword in xmp_list(xmp, "dc:subject") {
+        if !meta.keywords.contains(&keyword) {
+            meta.keywords.push(keyword);
+        }
+    }
+
+    let first = |name: &str| {
+        xmp_list(xmp, name)
+            .into_iter()
+            .next()
+            .or_else(|| xmp_value(xmp, name).filter(|v| !v.contains('<')))
+    };
+
+    if let Some(rights) = first("dc:rights") {
+        meta.copyright = Some(rights);
+    }
+    if let Some(title) = first("dc:title") {
+        meta.title = Some(title);
+    }
+    if let Some(description) = first("dc:description") {
+        meta.description = Some(description);
+    }
+    if meta.artist.is_none() {
+        meta.artist = first("dc:creator");
+    }
+}
+
+// Baca metadata EXIF, IPTC lalu XMP (XMP paling akhir supaya menang kalau ada konflik)
+pub fn read_image_metadata(path: &str, width: u32, height: u32, format: &str) -> ImageMetadata {
+    let mut meta = ImageMetadata {
+        width,
+        height,
+        format: format.to_string(),
+        ..Default::default()
+    };
+
+    // Tidak semua gambar punya EXIF, abaikan error-nya
+    let _ = apply_exif(&mut meta, path);
+
+    // Orientasi 5-8 memutar gambar 90 derajat: simpan ukuran sesuai tampilan
+    if matches!(meta.orientation, Some(5..=8)) {
+        std::mem::swap(&mut meta.width, &mut meta.height);
+    }
+
+    let mut bytes = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(MAX_SCAN_BYTES).read_to_end(&mut bytes);
+    }
+
+    for (dataset, value) in read_iptc(&bytes) {
+        match dataset {
+            5 => meta.title = meta.title.take().or(Some(value)),
+            25 if !meta.keywords.contains(&value) => meta.keywords.push(value),
+            80 => meta.artist = meta.artist.take().or(Some(value)),
+            116 => meta.copyright = meta.copyright.take().or(Some(value)),
+            120 => meta.description = meta.description.take().or(Some(value)),
+            _ => {}
+        }
+    }
+
+    if let (Some(start), Some(end)) = (find(&bytes, b"<x:xmpmeta"), find(&bytes, b"</x:xmpmeta>")) {
+        if end > start {
+            apply_xmp(&mut meta, &String::from_utf8_lossy(&bytes[start..end]));
+        }
+    }
+
+    meta
+}
+
+// Teks gabungan untuk kolom search_text (keyword, kamera, lensa, copyright, ...)
+pub fn image_search_text(meta: &ImageMetadata) -> String {
+    let mut parts: Vec<&str> = meta.keywords.iter().map(|k| k.as_str()).collect();
+
+    for value in [
+        &meta.title,
+        &meta.description,
+        &meta.camera_make,
+        &meta.camera_model,
+        &meta.lens,
+        &meta.artist,
+        &meta.copyright,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        parts.push(value);
+    }
+
+    parts.join(" ")
+}
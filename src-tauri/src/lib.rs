@@ -3,17 +3,32 @@ use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager, State};
 
 use crate::{
-    db_lib::is_schema_valid,
+    db_lib::{asset_from_row, is_schema_valid, ASSET_COLUMNS},
     ffmpeg::download_ffmpeg,
     models::{Asset, AssetMetadata, DbState, PaginatedResponse},
 };
+mod anim_lib;
+mod audio_meta_lib;
+mod classify_lib;
+mod color_lib;
+mod convert_lib;
 mod db_lib;
+mod feature_lib;
 mod ffmpeg;
 mod folder_lib;
 mod image_lib;
+mod image_meta_lib;
+mod loudness_lib;
 mod models;
+mod playback_lib;
+mod pro_image_lib;
+mod proxy_lib;
 mod sound_lib;
+mod svg_lib;
+mod thumb_cache_lib;
+mod ucs_lib;
 mod utils;
+mod video_lib;
 mod yt_dlp;
 
 #[tauri::command]
@@ -35,14 +50,21 @@ fn get_count_assets(state: State<'_, DbState>, asset_type: String) -> Result<u64
     Ok(count as u64)
 }
 
+// Argumen command = field payload invoke dari frontend
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 fn get_assets_paginated(
     state: State<'_, DbState>,
     page: u32,
     page_size: u32,
-    query: String,      // Search keyword (kosong string jika tidak search)
-    asset_type: String, // Filter: 'all', 'audio', 'video', 'image', 'sfx'
-) -> Result<PaginatedResponse, String> {
+    query: String,                // Search keyword (kosong string jika tidak search)
+    asset_type: String,           // Filter: 'all', 'audio', 'video', 'image', 'sfx'
+    ucs_category: Option<String>, // Browse per kategori UCS (opsional)
+    ucs_subcategory: Option<String>,
+    ucs_cat_id: Option<String>, // Browse CatID, termasuk yang tidak ada di tabel UCS
+    color: Option<String>,      // Search-by-color, hex "#rrggbb" (opsional)
+    color_distance: Option<f64>, // Jarak ΔE maksimum, default 20
+) -> Result<PaginatedResponse<Asset>, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
 
     let mut sql_base = "FROM assets WHERE 1=1".to_string();
@@ -53,20 +75,26 @@ fn get_assets_paginated(
         let tokens: Vec<&str> = query.split_whitespace().filter(|s| !s.is_empty()).collect();
 
         if !tokens.is_empty() {
-            // Build search condition for each token across filename and original_path
+            // Build search condition for each token across filename, original_path,
+            // embedded metadata text and tags the user has accepted
             let mut token_conditions = Vec::new();
             for _ in &tokens {
-                token_conditions.push("(filename LIKE ? OR original_path LIKE ?)");
+                token_conditions.push(
+                    "(filename LIKE ? OR original_path LIKE ? OR search_text LIKE ?
+                      OR id IN (SELECT asset_id FROM asset_tags WHERE status = 'accepted' AND tag LIKE ?))",
+                );
             }
 
             // Combine all token conditions with AND (all tokens must match)
             sql_base.push_str(&format!(" AND ({})", token_conditions.join(" AND ")));
 
-            // Add wildcard parameters for each token (2 params per token: filename and original_path)
+            // Add wildcard parameters for each token (4 params per token: filename,
+            // original_path, search_text, tag)
             for token in tokens {
                 let wildcard = format!("%{}%", token);
-                params_values.push(Box::new(wildcard.clone()));
-                params_values.push(Box::new(wildcard));
+                for _ in 0..4 {
+                    params_values.push(Box::new(wildcard.clone()));
+                }
             }
         }
     }
@@ -76,6 +104,38 @@ fn get_assets_paginated(
         params_values.push(Box::new(asset_type));
     }
 
+    if let Some(category) = ucs_category {
+        sql_base.push_str(" AND ucs_category = ?");
+        params_values.push(Box::new(category));
+    }
+
+    if let Some(subcategory) = ucs_subcategory {
+        sql_base.push_str(" AND ucs_subcategory = ?");
+        params_values.push(Box::new(subcategory));
+    }
+
+    if let Some(cat_id) = ucs_cat_id {
+        sql_base.push_str(" AND ucs_cat_id = ? COLLATE NOCASE");
+        params_values.push(Box::new(cat_id));
+    }
+
+    // Gambar yang salah satu warna dominannya dekat (ΔE di ruang Lab) dengan warna pilihan
+    if let Some(color) = color {
+        let (r, g, b) = color_lib::parse_hex_color(&color)?;
+        let [l, a, lab_b] = color_lib::rgb_to_lab(r, g, b);
+        let distance = color_distance.unwrap_or(color_lib::DEFAULT_COLOR_DISTANCE);
+
+        sql_base.push_str(
+            " AND id IN (SELECT asset_id FROM asset_colors
+               WHERE (lab_l - ?) * (lab_l - ?) + (lab_a - ?) * (lab_a - ?)
+                     + (lab_b - ?) * (lab_b - ?) <= ?)",
+        );
+        for value in [l, l, a, a, lab_b, lab_b] {
+            params_values.push(Box::new(value as f64));
+        }
+        params_values.push(Box::new(distance * distance));
+    }
+
     let sql_count = format!("SELECT COUNT(*) {}", sql_base);
 
     let params_refs: Vec<&dyn ToSql> = params_values.iter().map(|p| p.as_ref()).collect();
@@ -91,34 +151,17 @@ fn get_assets_paginated(
     let offset = (page.max(1) - 1) * page_size;
 
     let sql_data = format!(
-        "SELECT id, filename, extension, original_path, type, 
-                thumbnail_path, duration_sec, file_size, waveform_data, metadata
+        "SELECT {}
          {} 
          ORDER BY id ASC 
          LIMIT {} OFFSET {}",
-        sql_base, page_size, offset
+        ASSET_COLUMNS, sql_base, page_size, offset
     );
 
     let mut stmt = conn.prepare(&sql_data).map_err(|e| e.to_string())?;
 
     let asset_iter = stmt
-        .query_map(params_refs.as_slice(), |row| {
-            let waveform_str: String = row.get("waveform_data").unwrap_or("[]".to_string());
-            let metadata_str: String = row.get("metadata").unwrap_or("{}".to_string());
-
-            Ok(Asset {
-                id: row.get("id")?,
-                filename: row.get("filename")?,
-                extension: row.get("extension")?,
-                original_path: row.get("original_path")?,
-                type_name: row.get("type")?,
-                thumbnail_path: row.get("thumbnail_path")?,
-                duration_sec: row.get("duration_sec")?,
-                file_size: row.get("file_size")?,
-                waveform_data: serde_json::from_str(&waveform_str).unwrap_or_default(),
-                metadata: serde_json::from_str(&metadata_str).unwrap_or(AssetMetadata::None),
-            })
-        })
+        .query_map(params_refs.as_slice(), asset_from_row)
         .map_err(|e| e.to_string())?;
 
     let mut data = Vec::new();
@@ -190,6 +233,7 @@ pub fn run() {
 
             conn.pragma_update(None, "journal_mode", "WAL").unwrap();
             conn.pragma_update(None, "synchronous", "NORMAL").unwrap();
+            conn.pragma_update(None, "foreign_keys", "ON").unwrap();
 
             conn.execute(
                 "CREATE TABLE IF NOT EXISTS assets (
@@ -202,7 +246,21 @@ pub fn run() {
                     duration_sec    REAL DEFAULT 0,
                     file_size       INTEGER NOT NULL,
                     waveform_data   TEXT,
-                    metadata        TEXT
+                    waveform_rms    TEXT,
+                    metadata        TEXT,
+                    trim_start_sec  REAL,
+                    trim_end_sec    REAL,
+                    ucs_cat_id      TEXT,
+                    ucs_category    TEXT,
+                    ucs_subcategory TEXT,
+                    ucs_fx_name     TEXT,
+                    ucs_creator_id  TEXT,
+                    ucs_source_id   TEXT,
+                    search_text     TEXT,
+                    source_asset_id INTEGER REFERENCES assets(id) ON DELETE SET NULL,
+                    rating          INTEGER,
+                    blurhash        TEXT,
+                    thumbnail_key   TEXT
                 )",
                 [],
             )?;
@@ -213,10 +271,108 @@ pub fn run() {
                 [],
             )?;
 
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_assets_ucs
+                 ON assets(ucs_category, ucs_subcategory)",
+                [],
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_assets_thumbnail_key
+                 ON assets(thumbnail_key)",
+                [],
+            )?;
+
+            // Region hasil split file multi-hit, ikut terhapus saat asset induk dihapus
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS regions (
+                    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                    asset_id        INTEGER NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+                    region_index    INTEGER NOT NULL,
+                    start_sec       REAL NOT NULL,
+                    end_sec         REAL NOT NULL
+                )",
+                [],
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_regions_asset ON regions(asset_id)",
+                [],
+            )?;
+
+            // Shot hasil deteksi scene video; terpisah dari regions supaya tidak ikut
+            // muncul di browser region / export audio
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS shots (
+                    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                    asset_id        INTEGER NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+                    shot_index      INTEGER NOT NULL,
+                    start_sec       REAL NOT NULL,
+                    end_sec         REAL NOT NULL
+                )",
+                [],
+            )?;
+
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_shots_asset ON shots(asset_id)",
+                [],
+            )?;
+
+            // Fitur spektral (JSON AudioFeatures) untuk pencarian "sounds like this"
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS audio_features (
+                    asset_id        INTEGER PRIMARY KEY REFERENCES assets(id) ON DELETE CASCADE,
+                    features        TEXT NOT NULL
+                )",
+                [],
+            )?;
+
+            // Tag per asset, source 'auto' = saran classifier yang bisa diterima/ditolak,
+            // 'user' = tag manual
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS asset_tags (
+                    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                    asset_id        INTEGER NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+                    tag             TEXT NOT NULL,
+                    source          TEXT NOT NULL,
+                    confidence      REAL NOT NULL DEFAULT 1.0,
+                    status          TEXT NOT NULL DEFAULT 'suggested',
+                    UNIQUE(asset_id, tag)
+                )",
+                [],
+            )?;
+
+            // Proxy preview ringan; sidik jari sumber dipakai untuk invalidasi
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS proxies (
+                    asset_id        INTEGER PRIMARY KEY REFERENCES assets(id) ON DELETE CASCADE,
+                    proxy_path      TEXT NOT NULL,
+                    source_size     INTEGER NOT NULL,
+                    source_mtime    INTEGER NOT NULL
+                )",
+                [],
+            )?;
+
+            // Palette warna dominan gambar; nilai Lab untuk filter search-by-color
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS asset_colors (
+                    asset_id        INTEGER NOT NULL REFERENCES assets(id) ON DELETE CASCADE,
+                    color_index     INTEGER NOT NULL,
+                    hex             TEXT NOT NULL,
+                    lab_l           REAL NOT NULL,
+                    lab_a           REAL NOT NULL,
+                    lab_b           REAL NOT NULL,
+                    weight          REAL NOT NULL,
+                    PRIMARY KEY (asset_id, color_index)
+                )",
+                [],
+            )?;
+
             app.manage(DbState {
                 conn: Arc::new(Mutex::new(conn)),
                 cancel_scan: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             });
+            app.manage(playback_lib::PlayerState::default());
 
             Ok(())
         })
@@ -228,8 +384,52 @@ pub fn run() {
             yt_dlp::update_ytdlp,
             yt_dlp::run_ytdlp,
             db_lib::clear_db,
+            db_lib::set_asset_rating,
             sound_lib::generate_missing_waveforms,
+            audio_meta_lib::write_audio_metadata,
+            sound_lib::generate_trim_points,
+            sound_lib::export_trimmed_audio,
+            sound_lib::generate_regions,
+            sound_lib::get_asset_regions,
+            sound_lib::get_regions_paginated,
+            sound_lib::export_regions,
+            feature_lib::generate_missing_features,
+            feature_lib::find_similar_assets,
+            classify_lib::classify_audio_assets,
+            classify_lib::get_asset_tags,
+            classify_lib::set_tag_status,
+            classify_lib::add_asset_tag,
+            ucs_lib::get_ucs_categories,
+            convert_lib::convert_audio,
+            loudness_lib::export_normalized_audio,
+            playback_lib::list_output_devices,
+            playback_lib::set_output_device,
+            playback_lib::play_asset,
+            playback_lib::pause_playback,
+            playback_lib::resume_playback,
+            playback_lib::stop_playback,
+            playback_lib::seek_playback,
+            playback_lib::set_playback_loop,
+            playback_lib::get_playback_status,
+            proxy_lib::generate_proxies,
+            proxy_lib::get_playable_path,
+            video_lib::generate_video_metadata,
+            video_lib::generate_scrub_strips,
+            video_lib::get_scrub_strip,
+            video_lib::export_contact_sheet,
+            video_lib::detect_scenes,
+            video_lib::get_video_shots,
+            video_lib::export_video_subclip,
+            video_lib::extract_audio_from_videos,
             image_lib::generate_missing_thumbnails,
+            image_lib::regenerate_thumbnails,
+            image_lib::get_thumbnail_settings,
+            image_lib::get_thumbnail_path,
+            anim_lib::get_animated_preview,
+            color_lib::get_asset_palette,
+            thumb_cache_lib::gc_thumbnail_cache,
+            thumb_cache_lib::set_thumbnail_cache_limit,
+            thumb_cache_lib::move_thumbnail_cache,
             image_lib::cancel_scan,
             folder_lib::scan_and_import_folder,
             folder_lib::trigger_folder_watcher,
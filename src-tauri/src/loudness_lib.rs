@@ -0,0 +1,474 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    f64::consts::PI,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Deserialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+    folder_lib::import_single_file,
+    models::ProgressEvent,
+    sound_lib::{decode_audio, write_wav_range, DecodedAudio},
+    DbState,
+};
+
+const DEFAULT_TRUE_PEAK_CEILING_DB: f32 = -1.0;
+const DEFAULT_NAME_TEMPLATE: &str = "{name}_norm";
+
+// Gating BS.1770: blok 400 ms, overlap 75%
+const BLOCK_SEC: f64 = 0.4;
+const BLOCK_STEP_SEC: f64 = 0.1;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+const OVERSAMPLE: usize = 4;
+const TAPS_PER_PHASE: usize = 12;
+
+const LIMITER_LOOKAHEAD_SEC: f64 = 0.005;
+const LIMITER_RELEASE_SEC: f64 = 0.05;
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 3],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[1] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[2] * y;
+        y
+    }
+}
+
+// Filter K-weighting (high shelf + high pass) sesuai ITU-R BS.1770, dihitung ulang per sample rate
+fn k_weighting(sample_rate: u32) -> (Biquad, Biquad) {
+    let fs = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    (shelf, high_pass)
+}
+
+// Bobot kanal: surround +1.5 dB, LFE diabaikan (asumsi urutan 5.1 L R C LFE Ls Rs)
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6, 3) => 0.0,
+        (6, 4) | (6, 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+// Integrated loudness (LUFS). Return None untuk file hening total.
+pub fn measure_integrated_loudness(audio: &DecodedAudio) -> Option<f64> {
+    let channels = audio.channels;
+    let frames = audio.frames();
+    if audio.sample_rate == 0 || frames == 0 {
+        return None;
+    }
+
+    // 1. Energi K-weighted per frame (sudah dijumlah antar kanal dengan bobotnya)
+    let mut weighted = vec![0.0f64; frames];
+    for ch in 0..channels {
+        let weight = channel_weight(ch, channels);
+        if weight == 0.0 {
+            continue;
+        }
+
+        let (mut shelf, mut high_pass) = k_weighting(audio.sample_rate);
+        for (frame, energy) in weighted.iter_mut().enumerate() {
+            let x = audio.samples[frame * channels + ch] as f64;
+            let y = high_pass.process(shelf.process(x));
+            *energy += weight * y * y;
+        }
+    }
+
+    // 2. Energi rata-rata per blok 400 ms
+    let block_len = ((BLOCK_SEC * audio.sample_rate as f64) as usize).max(1);
+    let step = ((BLOCK_STEP_SEC * audio.sample_rate as f64) as usize).max(1);
+
+    let mut prefix = Vec::with_capacity(frames + 1);
+    prefix.push(0.0f64);
+    for e in &weighted {
+        prefix.push(prefix.last().unwrap() + e);
+    }
+
+    let mut blocks = Vec::new();
+    if frames < block_len {
+        // File lebih pendek dari satu blok dianggap satu blok
+        blocks.push(prefix[frames] / frames as f64);
+    } else {
+        let mut start = 0;
+        while start + block_len <= frames {
+            blocks.push((prefix[start + block_len] - prefix[start]) / block_len as f64);
+            start += step;
+        }
+    }
+
+    let loudness = |z: f64| -0.691 + 10.0 * z.log10();
+
+    // 3. Absolute gate lalu relative gate
+    let above_abs: Vec<f64> = blocks
+        .into_iter()
+        .filter(|z| *z > 0.0 && loudness(*z) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if above_abs.is_empty() {
+        return None;
+    }
+
+    let relative_gate =
+        loudness(above_abs.iter().sum::<f64>() / above_abs.len() as f64) + RELATIVE_GATE_LU;
+    let gated: Vec<f64> = above_abs
+        .into_iter()
+        .filter(|z| loudness(*z) > relative_gate)
+        .collect();
+    if gated.is_empty() {
+        return None;
+    }
+
+    Some(loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+// Koefisien interpolator polyphase (windowed sinc) untuk oversampling 4x
+fn oversampling_filter() -> Vec<Vec<f64>> {
+    let half = TAPS_PER_PHASE as f64 / 2.0;
+    (0..OVERSAMPLE)
+        .map(|phase| {
+            let frac = phase as f64 / OVERSAMPLE as f64;
+            (0..TAPS_PER_PHASE)
+                .map(|tap| {
+                    let t = tap as f64 - half + 1.0 - frac;
+                    let sinc = if t.abs() < 1e-9 {
+                        1.0
+                    } else {
+                        (PI * t).sin() / (PI * t)
+                    };
+                    let window = 0.5 + 0.5 * (PI * t / half).cos();
+                    sinc * window
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// True peak dalam dBTP (estimasi dengan oversampling 4x)
+pub fn measure_true_peak(audio: &DecodedAudio) -> f64 {
+    let channels = audio.channels;
+    let frames = audio.frames();
+    let filter = oversampling_filter();
+    let mut peak = 0.0f64;
+
+    for ch in 0..channels {
+        let sample_at = |i: isize| -> f64 {
+            if i < 0 || i as usize >= frames {
+                0.0
+            } else {
+                audio.samples[i as usize * channels + ch] as f64
+            }
+        };
+
+        for n in 0..frames as isize {
+            for coeffs in &filter {
+                let y: f64 = coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(tap, c)| {
+                        c * sample_at(n + tap as isize - TAPS_PER_PHASE as isize / 2 + 1)
+                    })
+                    .sum();
+                peak = peak.max(y.abs());
+            }
+        }
+    }
+
+    20.0 * peak.max(1e-10).log10()
+}
+
+fn apply_gain(audio: &mut DecodedAudio, gain_db: f64) {
+    let gain = 10f64.powf(gain_db / 20.0) as f32;
+    for s in audio.samples.iter_mut() {
+        *s *= gain;
+    }
+}
+
+// Brickwall limiter dengan lookahead: gain turun sebelum puncak, naik perlahan (release)
+fn apply_limiter(audio: &mut DecodedAudio, ceiling_db: f64) {
+    let channels = audio.channels;
+    let frames = audio.frames();
+    let ceiling = 10f64.powf(ceiling_db / 20.0) as f32;
+    let lookahead = ((LIMITER_LOOKAHEAD_SEC * audio.sample_rate as f64) as usize).max(1);
+    let release = 1.0 / (LIMITER_RELEASE_SEC * audio.sample_rate as f64).max(1.0) as f32;
+
+    // Gain maksimal yang diizinkan tiap frame
+    let required: Vec<f32> = audio
+        .samples
+        .chunks(channels)
+        .map(|frame| {
+            let peak = frame.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            if peak > ceiling {
+                ceiling / peak
+            } else {
+                1.0
+            }
+        })
+        .collect();
+
+    if required.iter().all(|g| *g >= 1.0) {
+        return;
+    }
+
+    // Minimum ke depan sepanjang lookahead (monotonic deque)
+    let mut ahead_min = vec![1.0f32; frames];
+    let mut window: VecDeque<usize> = VecDeque::new();
+    for i in (0..frames).rev() {
+        while window.back().is_some_and(|&j| required[j] >= required[i]) {
+            window.pop_back();
+        }
+        window.push_back(i);
+        while window.front().is_some_and(|&j| j >= i + lookahead) {
+            window.pop_front();
+        }
+        ahead_min[i] = required[*window.front().unwrap()];
+    }
+
+    // Release: gain naik perlahan, turun seketika
+    let mut current = 1.0f32;
+    for g in ahead_min.iter_mut() {
+        current = (current + (1.0 - current) * release).min(*g);
+        *g = current;
+    }
+
+    // Rata-rata sepanjang lookahead supaya attack halus; tetap <= gain yang dibutuhkan di puncak
+    let mut sum = 0.0f64;
+    for i in 0..frames {
+        sum += ahead_min[i] as f64;
+        if i >= lookahead {
+            sum -= ahead_min[i - lookahead] as f64;
+        }
+        let count = (i + 1).min(lookahead) as f64;
+        let gain = if i + 1 < lookahead {
+            ahead_min[i].min((sum / count) as f32)
+        } else {
+            (sum / count) as f32
+        };
+
+        for s in &mut audio.samples[i * channels..(i + 1) * channels] {
+            *s *= gain;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NormalizeOptions {
+    pub mode: String, // 'lufs' atau 'peak'
+    pub target: f32,  // LUFS atau dBTP (true peak) sesuai mode
+    pub true_peak_ceiling_db: Option<f32>,
+    pub output_dir: String,
+    pub name_template: Option<String>, // Token: {name}, {target}, {index}
+}
+
+// Hasil template dipakai langsung sebagai nama file di output_dir, jadi pemisah path
+// dan ".." dibuang supaya output tidak bisa keluar dari folder tujuan
+fn render_name(template: &str, stem: &str, target: f32, index: usize) -> String {
+    let name = template
+        .replace("{name}", stem)
+        .replace("{target}", &format!("{}", target))
+        .replace("{index}", &format!("{:03}", index + 1))
+        .replace(['/', '\\'], "_")
+        .replace("..", "_");
+
+    if name.trim().is_empty() {
+        stem.to_string()
+    } else {
+        name
+    }
+}
+
+// Nama output unik: template tanpa {name}/{index} (atau dua file dengan nama sama dari
+// folder berbeda) tidak boleh saling menimpa saat ditulis paralel, dan file yang sudah
+// ada di output_dir (termasuk file sumbernya sendiri) juga tidak boleh tertimpa.
+// Perbandingan case-insensitive untuk filesystem Windows/macOS.
+fn unique_name(name: String, output_dir: &Path, used: &mut HashSet<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while !used.insert(candidate.to_lowercase())
+        || output_dir.join(format!("{}.wav", candidate)).exists()
+    {
+        candidate = format!("{}_{}", name, n);
+        n += 1;
+    }
+    candidate
+}
+
+pub fn normalize_audio(audio: &mut DecodedAudio, options: &NormalizeOptions) -> Result<(), String> {
+    let ceiling = options
+        .true_peak_ceiling_db
+        .unwrap_or(DEFAULT_TRUE_PEAK_CEILING_DB) as f64;
+
+    let gain_db = match options.mode.as_str() {
+        "lufs" => {
+            let loudness =
+                measure_integrated_loudness(audio).ok_or("File terlalu pelan untuk diukur")?;
+            options.target as f64 - loudness
+        }
+        "peak" => options.target as f64 - measure_true_peak(audio),
+        other => return Err(format!("Mode normalisasi {} tidak dikenal", other)),
+    };
+
+    apply_gain(audio, gain_db);
+    apply_limiter(audio, ceiling);
+
+    // Limiter bekerja di sample peak, sisa inter-sample peak diturunkan dengan gain statis
+    let true_peak = measure_true_peak(audio);
+    if true_peak > ceiling {
+        apply_gain(audio, ceiling - true_peak);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn export_normalized_audio(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Vec<i64>,
+    options: NormalizeOptions,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+
+    if !matches!(options.mode.as_str(), "lufs" | "peak") {
+        return Err(format!("Mode normalisasi {} tidak dikenal", options.mode));
+    }
+    std::fs::create_dir_all(&options.output_dir).map_err(|e| e.to_string())?;
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(usize, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT original_path, filename FROM assets WHERE id = ?1 AND type = 'audio'")
+            .map_err(|e| e.to_string())?;
+
+        asset_ids
+            .iter()
+            .filter_map(|id| {
+                stmt.query_row([id], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .ok()
+            })
+            .enumerate()
+            .map(|(index, (path, filename))| (index, path, filename))
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Tidak ada file audio untuk dinormalisasi.".to_string());
+    }
+
+    let template = options
+        .name_template
+        .clone()
+        .unwrap_or(DEFAULT_NAME_TEMPLATE.to_string());
+
+    // Path output ditentukan di awal (berurutan) supaya de-duplikasi nama deterministik
+    let output_dir = PathBuf::from(&options.output_dir);
+    let mut used_names = HashSet::new();
+    let to_process: Vec<(PathBuf, String, String)> = to_process
+        .into_iter()
+        .map(|(index, path, filename)| {
+            let stem = Path::new(&path)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let name = unique_name(
+                render_name(&template, &stem, options.target, index),
+                &output_dir,
+                &mut used_names,
+            );
+            let out_path = output_dir.join(format!("{}.wav", name));
+            (out_path, path, filename)
+        })
+        .collect();
+
+    let processed_count = std::sync::Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process
+            .par_iter()
+            .for_each(|(out_path, path, filename)| {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+                let _ = app.emit(
+                    "normalize-progress",
+                    ProgressEvent {
+                        name: "Normalize".to_string(),
+                        current,
+                        total: total_files,
+                        filename: filename.clone(),
+                        status: "processing".to_string(),
+                    },
+                );
+
+                let result = decode_audio(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut audio| {
+                        normalize_audio(&mut audio, &options)?;
+                        write_wav_range(&audio, 0, audio.frames(), out_path)?;
+                        import_single_file(&db_arc, out_path)
+                    });
+
+                if let Err(e) = result {
+                    println!("Gagal normalisasi {}: {}", filename, e);
+                }
+            });
+
+        let _ = app.emit(
+            "normalize-progress",
+            ProgressEvent {
+                name: "Normalize".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!("Memulai normalisasi {} file audio...", total_files))
+}
@@ -6,24 +6,138 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)] // Biar otomatis deteksi varian berdasarkan isi field-nya
 pub enum AssetMetadata {
-    Audio {
-        sample_rate: u32,
-        bitrate: u32,
-        artist: Option<String>,
-    },
-    Video {
-        width: u32,
-        height: u32,
-        fps: f32,
-    },
-    Image {
-        width: u32,
-        height: u32,
-        format: String,
-    },
+    Audio(Box<AudioMetadata>),
+    Video(Box<VideoMetadata>),
+    Image(Box<ImageMetadata>),
     None,
 }
 
+// Hasil ffprobe. width/height adalah ukuran coded; rotation (derajat, searah jarum jam)
+// menentukan orientasi saat diputar.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VideoMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    #[serde(default)]
+    pub codec: Option<String>,
+    #[serde(default)]
+    pub bitrate: Option<u32>, // kbps
+    #[serde(default)]
+    pub pixel_format: Option<String>,
+    #[serde(default)]
+    pub color_space: Option<String>,
+    #[serde(default)]
+    pub audio_tracks: u32,
+    #[serde(default)]
+    pub rotation: i32,
+    #[serde(default)]
+    pub creation_time: Option<String>,
+}
+
+// Dimensi (sesuai tampilan, orientasi EXIF sudah diterapkan) + metadata EXIF/IPTC/XMP
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub format: String,
+    #[serde(default)]
+    pub bit_depth: Option<u32>, // Per channel
+    #[serde(default)]
+    pub layer_count: Option<u32>, // PSD/EXR
+    #[serde(default)]
+    pub page_count: Option<u32>, // TIFF multi-page
+    #[serde(default)]
+    pub frame_count: Option<u32>, // GIF/WebP/APNG animasi
+    #[serde(default)]
+    pub loop_count: Option<u32>, // 0 = loop terus
+    #[serde(default)]
+    pub duration_sec: Option<f64>,
+    #[serde(default)]
+    pub camera_make: Option<String>,
+    #[serde(default)]
+    pub camera_model: Option<String>,
+    #[serde(default)]
+    pub lens: Option<String>,
+    #[serde(default)]
+    pub iso: Option<u32>,
+    #[serde(default)]
+    pub shutter_speed: Option<String>, // "1/250" atau "2s"
+    #[serde(default)]
+    pub aperture: Option<f32>, // f-number
+    #[serde(default)]
+    pub focal_length_mm: Option<f32>,
+    #[serde(default)]
+    pub capture_date: Option<String>, // "YYYY-MM-DD HH:MM:SS"
+    #[serde(default)]
+    pub gps_latitude: Option<f64>,
+    #[serde(default)]
+    pub gps_longitude: Option<f64>,
+    #[serde(default)]
+    pub gps_altitude: Option<f64>,
+    #[serde(default)]
+    pub orientation: Option<u32>, // Nilai EXIF 1-8
+    #[serde(default)]
+    pub copyright: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub rating: Option<i32>, // XMP rating 0-5 (-1 = rejected)
+}
+
+// Info stream + metadata embedded (ID3, Vorbis comment, RIFF INFO, BWF bext, iXML)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioMetadata {
+    pub sample_rate: u32,
+    pub bitrate: u32, // kbps
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub channels: Option<u32>,
+    #[serde(default)]
+    pub bit_depth: Option<u32>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub album: Option<String>,
+    #[serde(default)]
+    pub genre: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+
+    // BWF bext
+    #[serde(default)]
+    pub originator: Option<String>,
+    #[serde(default)]
+    pub originator_reference: Option<String>,
+    #[serde(default)]
+    pub origination_date: Option<String>,
+    #[serde(default)]
+    pub time_reference: Option<u64>, // Timecode dalam sample sejak tengah malam
+
+    // iXML
+    #[serde(default)]
+    pub ixml_project: Option<String>,
+    #[serde(default)]
+    pub ixml_scene: Option<String>,
+    #[serde(default)]
+    pub ixml_take: Option<String>,
+    #[serde(default)]
+    pub ixml_tape: Option<String>,
+    #[serde(default)]
+    pub ixml_note: Option<String>,
+}
+
 #[derive(Clone, Serialize)]
 pub struct ProgressEvent {
     pub name: String,
@@ -33,6 +147,24 @@ pub struct ProgressEvent {
     pub status: String, // "processing" atau "done"
 }
 
+// Hasil parse nama file Universal Category System
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UcsInfo {
+    pub cat_id: String,
+    pub category: Option<String>, // Option karena dibaca dari kolom DB yang nullable
+    pub subcategory: Option<String>,
+    pub fx_name: String,
+    pub creator_id: Option<String>,
+    pub source_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UcsCategoryCount {
+    pub category: String,
+    pub subcategory: Option<String>,
+    pub count: u64,
+}
+
 // Struct Utama
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Asset {
@@ -48,14 +180,110 @@ pub struct Asset {
 
     // Waveform disimpan sebagai bytes binary
     pub waveform_data: Option<Vec<f32>>,
+    // RMS per bar (sejajar waveform_data) untuk melihat bagian yang keras/padat
+    pub waveform_rms: Option<Vec<f32>>,
 
     // Metadata fleksibel
     pub metadata: AssetMetadata,
+
+    // Saran in/out point hasil deteksi silence (detik)
+    pub trim_start_sec: Option<f64>,
+    pub trim_end_sec: Option<f64>,
+
+    // Terisi kalau nama file mengikuti format UCS
+    pub ucs: Option<UcsInfo>,
+
+    // Asset asal, mis. video sumber untuk audio hasil extract
+    pub source_asset_id: Option<i64>,
+
+    // 0-5, -1 = rejected (bisa diimpor dari XMP)
+    pub rating: Option<i32>,
+
+    // Placeholder BlurHash untuk grid sebelum thumbnail selesai dimuat
+    pub blurhash: Option<String>,
 }
 
+// Potongan hit di dalam satu file audio multi-hit
 #[derive(Debug, Serialize)]
-pub struct PaginatedResponse {
-    pub data: Vec<Asset>,
+pub struct Region {
+    pub id: i64,
+    pub asset_id: i64,
+    pub region_index: i64,
+    pub start_sec: f64,
+    pub end_sec: f64,
+
+    // Info asset induk supaya region bisa langsung diputar
+    pub filename: String,
+    pub extension: String,
+    pub original_path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AssetTag {
+    pub id: i64,
+    pub asset_id: i64,
+    pub tag: String,
+    pub source: String, // 'auto' atau 'user'
+    pub confidence: f64,
+    pub status: String, // 'suggested', 'accepted' atau 'rejected'
+}
+
+// Sprite sheet frame video untuk hover-scrubbing; frame ke-i ada di
+// kolom i % columns, baris i / columns
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScrubStrip {
+    pub sprite_path: String,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub timestamps: Vec<f64>,
+}
+
+// Warna dominan gambar, weight = porsi pixel (0-1)
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteColor {
+    pub hex: String, // "#rrggbb"
+    pub weight: f32,
+}
+
+// Lebar thumbnail yang dibuat (px) + quality WebP lossy 0-100
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailSettings {
+    pub sizes: Vec<u32>,
+    pub quality: f32,
+    // None = folder thumbnails di app data
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+    // None = tanpa batas
+    #[serde(default)]
+    pub max_cache_mb: Option<u64>,
+}
+
+// Hasil pembersihan cache thumbnail (file yatim + file yang dibuang karena batas ukuran)
+#[derive(Debug, Serialize)]
+pub struct ThumbnailCacheReport {
+    pub cache_dir: String,
+    pub removed_files: usize,
+    pub reclaimed_bytes: u64,
+    pub total_bytes: u64, // Ukuran cache setelah dibersihkan
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayablePath {
+    pub path: String,
+    pub is_proxy: bool,
+    pub needs_proxy: bool, // true kalau proxy disarankan tapi belum ada
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimilarAsset {
+    pub asset: Asset,
+    pub distance: f32, // Makin kecil makin mirip
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedResponse<T> {
+    pub data: Vec<T>,
     pub total_items: u64,
     pub total_pages: u64,
     pub current_page: u32,
@@ -71,3 +299,16 @@ pub struct ApiResponse {
     pub message: String,
     pub status: String,
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackStatus {
+    pub asset_id: Option<i64>,
+    pub region_id: Option<i64>,
+    pub position_sec: f64,
+    pub duration_sec: f64,
+    pub range_start_sec: f64, // Batas region (atau seluruh file)
+    pub range_end_sec: f64,
+    pub playing: bool,
+    pub looping: bool,
+    pub device: String,
+}
@@ -0,0 +1,885 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::{Time, TimeBase};
+use symphonia::default::{get_codecs, get_probe};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{models::PlaybackStatus, DbState};
+
+// Device tanpa output suara: posisi tetap jalan sesuai waktu nyata (untuk test headless)
+pub const NULL_DEVICE: &str = "null";
+const DEFAULT_DEVICE: &str = "default";
+
+// Interval event posisi ke UI
+const POSITION_INTERVAL: Duration = Duration::from_millis(30);
+const NULL_SAMPLE_RATE: u32 = 48000;
+const NULL_CHANNELS: usize = 2;
+
+// Audio yang di-decode di depan posisi playback (detik); sisanya menunggu
+const BUFFER_SEC: f64 = 2.0;
+// Jeda thread decode saat buffer penuh atau menunggu seek
+const DECODE_IDLE: Duration = Duration::from_millis(10);
+
+// Reader + decoder symphonia untuk satu file, dibaca per paket
+struct Source {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    sample_rate: u32,
+    channels: usize,
+    total_frames: Option<u64>,
+    // Seek symphonia mendarat di awal paket; frame sebelum target dibuang
+    skip_to: u64,
+}
+
+impl Source {
+    fn open(path: &str) -> Result<Source, String> {
+        let src = File::open(Path::new(path)).map_err(|e| e.to_string())?;
+        let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+        let mut hint = Hint::new();
+        if let Some(ext) = Path::new(path).extension() {
+            hint.with_extension(&ext.to_string_lossy());
+        }
+
+        let probed = get_probe()
+            .format(
+                &hint,
+                mss,
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .map_err(|e| e.to_string())?;
+        let format = probed.format;
+        let track = format.default_track().ok_or("Tidak ada track audio")?;
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+
+        let (Some(sample_rate), Some(channels)) = (params.sample_rate, params.channels) else {
+            return Err("Format audio tidak didukung untuk playback".to_string());
+        };
+        let decoder = get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .map_err(|e| e.to_string())?;
+
+        Ok(Source {
+            format,
+            decoder,
+            track_id,
+            time_base: params.time_base,
+            sample_rate,
+            channels: channels.count().max(1),
+            total_frames: params.n_frames,
+            skip_to: 0,
+        })
+    }
+
+    fn ts_to_frame(&self, ts: u64) -> u64 {
+        match self.time_base {
+            Some(tb) => {
+                let time = tb.calc_time(ts);
+                ((time.seconds as f64 + time.frac) * self.sample_rate as f64).round() as u64
+            }
+            None => ts,
+        }
+    }
+
+    fn seek(&mut self, frame: u64) {
+        let time = Time::from(frame as f64 / self.sample_rate as f64);
+        // Kalau seek gagal, decode lanjut dari posisi sekarang dan skip_to membuang sisanya
+        let _ = self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::Time {
+                time,
+                track_id: Some(self.track_id),
+            },
+        );
+        self.decoder.reset();
+        self.skip_to = frame;
+    }
+
+    // Jumlah frame untuk file yang header-nya tidak mencatat durasi (jumlahkan durasi paket, tanpa decode)
+    fn count_frames(&mut self) -> u64 {
+        let mut total = 0;
+        while let Ok(packet) = self.format.next_packet() {
+            if packet.track_id() == self.track_id {
+                total += packet.dur();
+            }
+        }
+        self.seek(0);
+        self.ts_to_frame(total)
+    }
+
+    // Decode paket berikutnya: (frame sumber pertama, sample interleaved). None di akhir file.
+    fn next_chunk(&mut self) -> Option<(u64, Vec<f32>)> {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::ResetRequired) => {
+                    self.decoder.reset();
+                    continue;
+                }
+                Err(_) => return None,
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // Paket rusak dilewati saja, bukan akhir file
+                Err(Error::DecodeError(_)) => continue,
+                Err(_) => return None,
+            };
+            let spec = *decoded.spec();
+            if decoded.frames() == 0 || spec.channels.count() != self.channels {
+                continue;
+            }
+
+            let mut sample_buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+            sample_buf.copy_interleaved_ref(decoded);
+            let mut samples = sample_buf.samples().to_vec();
+
+            let mut start = self.ts_to_frame(packet.ts());
+            let frames = (samples.len() / self.channels) as u64;
+            if start + frames <= self.skip_to {
+                continue;
+            }
+            if start < self.skip_to {
+                samples.drain(..(self.skip_to - start) as usize * self.channels);
+                start = self.skip_to;
+            }
+            return Some((start, samples));
+        }
+    }
+}
+
+// Potongan audio hasil decode, dikirim dari thread decode ke callback output
+struct Chunk {
+    epoch: u64,
+    start: u64, // Frame sumber pertama
+    samples: Vec<f32>,
+    last: bool, // Ujung range tanpa loop: tidak ada data lagi sampai seek berikutnya
+}
+
+// Perintah untuk thread decode; dikunci terpisah dari Playback supaya callback audio tidak rebutan
+#[derive(Default)]
+struct DecodeControl {
+    seek: Option<(u64, u64)>, // (epoch, frame)
+    range: (f64, f64),
+    looping: bool,
+    stop: bool,
+}
+
+// Thread decode: isi buffer sampai BUFFER_SEC di depan posisi, wrap sendiri saat loop
+fn spawn_decode_thread(
+    mut source: Source,
+    control: Arc<Mutex<DecodeControl>>,
+    chunks: Sender<Chunk>,
+    buffered: Arc<AtomicUsize>,
+) {
+    let capacity = (BUFFER_SEC * source.sample_rate as f64) as usize;
+    let channels = source.channels;
+
+    std::thread::spawn(move || {
+        let mut epoch = 0;
+        // Menunggu seek pertama / ujung range sudah tercapai
+        let mut idle = true;
+        // Ada frame sejak seek/wrap terakhir (cegah wrap tanpa henti pada range kosong)
+        let mut produced = false;
+
+        loop {
+            let (range, looping) = {
+                let Ok(mut control) = control.lock() else {
+                    return;
+                };
+                if control.stop {
+                    return;
+                }
+                if let Some((next_epoch, frame)) = control.seek.take() {
+                    epoch = next_epoch;
+                    source.seek(frame);
+                    idle = false;
+                    produced = false;
+                }
+                (control.range, control.looping)
+            };
+
+            if idle || buffered.load(Ordering::SeqCst) >= capacity {
+                std::thread::sleep(DECODE_IDLE);
+                continue;
+            }
+
+            let end = range.1.ceil() as u64;
+            let (start, mut samples) = source.next_chunk().unwrap_or((end, Vec::new()));
+            let mut last = start + (samples.len() / channels) as u64 >= end;
+            if last {
+                samples.truncate(end.saturating_sub(start) as usize * channels);
+            }
+            produced |= !samples.is_empty();
+
+            if last {
+                if looping && produced {
+                    source.seek(range.0 as u64);
+                    produced = false;
+                    last = false;
+                } else {
+                    idle = true;
+                }
+            }
+            if samples.is_empty() && !last {
+                continue;
+            }
+
+            buffered.fetch_add(samples.len() / channels, Ordering::SeqCst);
+            let chunk = Chunk {
+                epoch,
+                start,
+                samples,
+                last,
+            };
+            if chunks.send(chunk).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+struct Track {
+    asset_id: i64,
+    region_id: Option<i64>,
+    channels: usize,
+    sample_rate: u32,
+    frames: f64,
+    chunks: Receiver<Chunk>,
+    queue: VecDeque<Chunk>,
+    offset: usize, // Frame yang sudah diputar dari chunk terdepan
+    phase: f64,    // Pecahan antar frame (resampling)
+    epoch: u64,
+    ended: bool,
+    buffered: Arc<AtomicUsize>,
+    control: Arc<Mutex<DecodeControl>>,
+}
+
+impl Drop for Track {
+    fn drop(&mut self) {
+        if let Ok(mut control) = self.control.lock() {
+            control.stop = true;
+        }
+    }
+}
+
+impl Track {
+    fn release(&self, chunk: &Chunk) {
+        self.buffered
+            .fetch_sub(chunk.samples.len() / self.channels, Ordering::SeqCst);
+    }
+
+    // Ambil chunk baru dari thread decode; sisa dari sebelum seek dibuang
+    fn receive(&mut self) {
+        while let Ok(chunk) = self.chunks.try_recv() {
+            if chunk.epoch == self.epoch {
+                self.queue.push_back(chunk);
+            } else {
+                self.release(&chunk);
+            }
+        }
+    }
+
+    // Lepas chunk yang sudah habis diputar; true kalau masih ada frame siap
+    fn ready(&mut self) -> bool {
+        loop {
+            let Some(chunk) = self.queue.front() else {
+                return false;
+            };
+            let frames = chunk.samples.len() / self.channels;
+            if self.offset < frames {
+                return true;
+            }
+            self.offset -= frames;
+            if let Some(chunk) = self.queue.pop_front() {
+                self.ended |= chunk.last;
+                self.release(&chunk);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Playback {
+    track: Option<Track>,
+    position: f64, // Dalam frame sumber (pecahan, untuk resampling)
+    range: (f64, f64),
+    playing: bool,
+    looping: bool,
+}
+
+impl Playback {
+    // Isi buffer output (interleaved) dari chunk yang sudah di-decode dan majukan posisi.
+    // Resampling linear ke sample rate device, kanal dipetakan apa adanya.
+    fn render(&mut self, out: &mut [f32], out_channels: usize, out_rate: u32) {
+        out.fill(0.0);
+
+        let Some(track) = self.track.as_mut() else {
+            return;
+        };
+        track.receive();
+        if !self.playing || out_channels == 0 {
+            return;
+        }
+
+        let step = track.sample_rate as f64 / out_rate as f64;
+        let channels = track.channels;
+
+        for frame in out.chunks_mut(out_channels) {
+            if !track.ready() {
+                if track.ended {
+                    self.position = self.range.1;
+                    self.playing = false;
+                }
+                // Decode belum menyusul: hening dan posisi tidak maju
+                return;
+            }
+
+            let chunk = &track.queue[0];
+            let index = track.offset * channels;
+            let next = if index + channels < chunk.samples.len() {
+                Some(&chunk.samples[index + channels..])
+            } else {
+                track
+                    .queue
+                    .get(1)
+                    .filter(|c| !c.samples.is_empty())
+                    .map(|c| &c.samples[..])
+            };
+            let frac = track.phase as f32;
+
+            for (c, out_sample) in frame.iter_mut().enumerate() {
+                let ch = c.min(channels - 1);
+                let a = chunk.samples[index + ch];
+                let b = next.map(|n| n[ch]).unwrap_or(a);
+                *out_sample = a + (b - a) * frac;
+            }
+
+            let chunk_start = chunk.start as f64;
+            track.phase += step;
+            let whole = track.phase.floor();
+            track.phase -= whole;
+            track.offset += whole as usize;
+            self.position = chunk_start + track.offset as f64 + track.phase;
+        }
+    }
+
+    // Pindah posisi: buang buffer lama dan minta thread decode seek ke frame tersebut
+    fn seek(&mut self, frame: f64) {
+        // Loop yang sudah di ujung range langsung mulai dari awal range
+        let frame = if self.looping && frame >= self.range.1 {
+            self.range.0
+        } else {
+            frame
+        };
+        self.position = frame;
+
+        let Some(track) = self.track.as_mut() else {
+            return;
+        };
+        track.epoch += 1;
+        for chunk in std::mem::take(&mut track.queue) {
+            track.release(&chunk);
+        }
+        track.offset = 0;
+        track.phase = frame.fract();
+        track.ended = false;
+
+        if let Ok(mut control) = track.control.lock() {
+            control.seek = Some((track.epoch, frame as u64));
+            control.range = self.range;
+            control.looping = self.looping;
+        };
+    }
+
+    fn finished(&self) -> bool {
+        self.position >= self.range.1
+            || self
+                .track
+                .as_ref()
+                .is_some_and(|t| t.ended && t.queue.is_empty())
+    }
+
+    fn status(&self, device: &str) -> PlaybackStatus {
+        let (rate, duration) = match &self.track {
+            Some(track) => (
+                track.sample_rate as f64,
+                track.frames / track.sample_rate as f64,
+            ),
+            None => (1.0, 0.0),
+        };
+
+        PlaybackStatus {
+            asset_id: self.track.as_ref().map(|t| t.asset_id),
+            region_id: self.track.as_ref().and_then(|t| t.region_id),
+            position_sec: self.position / rate,
+            duration_sec: duration,
+            range_start_sec: self.range.0 / rate,
+            range_end_sec: self.range.1 / rate,
+            playing: self.playing,
+            looping: self.looping,
+            device: device.to_string(),
+        }
+    }
+}
+
+struct OutputHandle {
+    device: String,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+pub struct PlayerState {
+    playback: Arc<Mutex<Playback>>,
+    output: Mutex<Option<OutputHandle>>,
+    device: Mutex<String>,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        PlayerState {
+            playback: Arc::new(Mutex::new(Playback::default())),
+            output: Mutex::new(None),
+            device: Mutex::new(DEFAULT_DEVICE.to_string()),
+        }
+    }
+}
+
+impl PlayerState {
+    fn device_name(&self) -> String {
+        self.device
+            .lock()
+            .map(|d| d.clone())
+            .unwrap_or(DEFAULT_DEVICE.to_string())
+    }
+
+    fn status(&self) -> Result<PlaybackStatus, String> {
+        let playback = self.playback.lock().map_err(|e| e.to_string())?;
+        Ok(playback.status(&self.device_name()))
+    }
+
+    // Jalankan thread output untuk device terpilih kalau belum jalan
+    fn ensure_output(&self, app: &AppHandle) -> Result<(), String> {
+        let device = self.device_name();
+        let mut output = self.output.lock().map_err(|e| e.to_string())?;
+
+        if let Some(handle) = output.as_ref() {
+            if handle.device == device && !handle.thread.is_finished() {
+                return Ok(());
+            }
+        }
+        if let Some(handle) = output.take() {
+            handle.stop.store(true, Ordering::SeqCst);
+            let _ = handle.thread.join();
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = spawn_output_thread(
+            app.clone(),
+            device.clone(),
+            self.playback.clone(),
+            stop.clone(),
+        )?;
+
+        *output = Some(OutputHandle {
+            device,
+            stop,
+            thread,
+        });
+        Ok(())
+    }
+
+    fn stop_output(&self) -> Result<(), String> {
+        let handle = self.output.lock().map_err(|e| e.to_string())?.take();
+        if let Some(handle) = handle {
+            handle.stop.store(true, Ordering::SeqCst);
+            let _ = handle.thread.join();
+        }
+        Ok(())
+    }
+}
+
+fn find_output_device(name: &str) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+
+    if name == DEFAULT_DEVICE {
+        return host
+            .default_output_device()
+            .ok_or("Tidak ada output device audio".to_string());
+    }
+
+    host.output_devices()
+        .map_err(|e| e.to_string())?
+        .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+        .ok_or(format!("Output device {} tidak ditemukan", name))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    playback: Arc<Mutex<Playback>>,
+) -> Result<cpal::Stream, String>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let rate = config.sample_rate.0;
+    let mut buffer: Vec<f32> = Vec::new();
+
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                buffer.resize(data.len(), 0.0);
+                // Jangan blok thread audio; kalau state sedang dipegang, isi hening dulu
+                match playback.try_lock() {
+                    Ok(mut playback) => playback.render(&mut buffer, channels, rate),
+                    Err(_) => buffer.fill(0.0),
+                }
+                for (out, sample) in data.iter_mut().zip(&buffer) {
+                    *out = T::from_sample(*sample);
+                }
+            },
+            |err| println!("Error output audio: {}", err),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+fn open_stream(device_name: &str, playback: Arc<Mutex<Playback>>) -> Result<cpal::Stream, String> {
+    let device = find_output_device(device_name)?;
+    let config = device.default_output_config().map_err(|e| e.to_string())?;
+
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.into(), playback),
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.into(), playback),
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.into(), playback),
+        other => Err(format!("Format sample {} tidak didukung", other)),
+    }?;
+
+    stream.play().map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+// Thread output: pegang stream cpal (atau clock untuk null device) dan kirim posisi ke UI
+fn spawn_output_thread(
+    app: AppHandle,
+    device: String,
+    playback: Arc<Mutex<Playback>>,
+    stop: Arc<AtomicBool>,
+) -> Result<JoinHandle<()>, String> {
+    // Stream cpal tidak Send, jadi dibuat di dalam thread; error dikirim balik lewat channel
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<(), String>>();
+
+    let thread = std::thread::spawn(move || {
+        let stream = if device == NULL_DEVICE {
+            None
+        } else {
+            match open_stream(&device, playback.clone()) {
+                Ok(stream) => Some(stream),
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            }
+        };
+        let _ = ready_tx.send(Ok(()));
+
+        let mut null_buffer = Vec::new();
+        let mut last_tick = Instant::now();
+        let mut was_playing = false;
+
+        while !stop.load(Ordering::SeqCst) {
+            std::thread::sleep(POSITION_INTERVAL);
+
+            let status = {
+                let Ok(mut playback) = playback.lock() else {
+                    break;
+                };
+
+                if stream.is_none() {
+                    // Null device: render ke buffer buangan sesuai waktu yang berlalu
+                    let elapsed = last_tick.elapsed().as_secs_f64();
+                    let frames = (elapsed * NULL_SAMPLE_RATE as f64) as usize;
+                    null_buffer.resize(frames * NULL_CHANNELS, 0.0);
+                    playback.render(&mut null_buffer, NULL_CHANNELS, NULL_SAMPLE_RATE);
+                }
+                last_tick = Instant::now();
+
+                let status = playback.status(&device);
+                // Kirim saat sedang main, plus sekali saat berhenti
+                if !status.playing && !was_playing {
+                    None
+                } else {
+                    Some(status)
+                }
+            };
+
+            if let Some(status) = status {
+                was_playing = status.playing;
+                let _ = app.emit("playback-position", status);
+            }
+        }
+
+        drop(stream);
+    });
+
+    ready_rx.recv().map_err(|e| e.to_string())?.map(|_| thread)
+}
+
+#[tauri::command]
+pub fn list_output_devices() -> Result<Vec<String>, String> {
+    let host = cpal::default_host();
+    let mut devices = vec![DEFAULT_DEVICE.to_string(), NULL_DEVICE.to_string()];
+
+    if let Ok(outputs) = host.output_devices() {
+        devices.extend(outputs.filter_map(|d| d.name().ok()));
+    }
+
+    Ok(devices)
+}
+
+#[tauri::command]
+pub fn set_output_device(
+    app: AppHandle,
+    player: State<'_, PlayerState>,
+    device: String,
+) -> Result<PlaybackStatus, String> {
+    if device != NULL_DEVICE {
+        find_output_device(&device)?;
+    }
+
+    *player.device.lock().map_err(|e| e.to_string())? = device;
+
+    // Pindahkan output yang sedang jalan ke device baru
+    let running = player.output.lock().map_err(|e| e.to_string())?.is_some();
+    if running {
+        player.ensure_output(&app)?;
+    }
+
+    player.status()
+}
+
+// Putar asset (atau satu region-nya). start_sec relatif terhadap awal file.
+#[tauri::command]
+pub async fn play_asset(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    player: State<'_, PlayerState>,
+    asset_id: i64,
+    region_id: Option<i64>,
+    start_sec: Option<f64>,
+    looping: Option<bool>,
+) -> Result<PlaybackStatus, String> {
+    let (path, stored_duration, region): (String, f64, Option<(f64, f64)>) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let (path, stored_duration) = conn
+            .query_row(
+                "SELECT original_path, COALESCE(duration_sec, 0) FROM assets WHERE id = ?1 AND type = 'audio'",
+                [asset_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| format!("Asset tidak ditemukan: {}", e))?;
+
+        let region = match region_id {
+            Some(region_id) => Some(
+                conn.query_row(
+                    "SELECT start_sec, end_sec FROM regions WHERE id = ?1 AND asset_id = ?2",
+                    [region_id, asset_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .map_err(|e| format!("Region tidak ditemukan: {}", e))?,
+            ),
+            None => None,
+        };
+
+        (path, stored_duration, region)
+    };
+
+    // Cukup buka header; audio di-decode bertahap oleh thread decode
+    let (source, frames) = tauri::async_runtime::spawn_blocking(move || {
+        let mut source = Source::open(&path)?;
+        let frames = match source.total_frames {
+            Some(frames) => frames,
+            None if stored_duration > 0.0 => (stored_duration * source.sample_rate as f64) as u64,
+            None => source.count_frames(),
+        };
+        Ok::<_, String>((source, frames))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if frames == 0 {
+        return Err("File audio kosong".to_string());
+    }
+
+    let rate = source.sample_rate as f64;
+    let frames = frames as f64;
+    let (chunk_tx, chunk_rx) = std::sync::mpsc::channel();
+    let control = Arc::new(Mutex::new(DecodeControl::default()));
+    let buffered = Arc::new(AtomicUsize::new(0));
+    let track = Track {
+        asset_id,
+        region_id,
+        channels: source.channels,
+        sample_rate: source.sample_rate,
+        frames,
+        chunks: chunk_rx,
+        queue: VecDeque::new(),
+        offset: 0,
+        phase: 0.0,
+        epoch: 0,
+        ended: false,
+        buffered: buffered.clone(),
+        control: control.clone(),
+    };
+    spawn_decode_thread(source, control, chunk_tx, buffered);
+
+    {
+        let mut playback = player.playback.lock().map_err(|e| e.to_string())?;
+        // Track lama di-drop di sini, thread decode-nya ikut berhenti
+        playback.track = Some(track);
+
+        let (start, end) = match region {
+            Some((start, end)) => ((start * rate).max(0.0), (end * rate).min(frames)),
+            None => (0.0, frames),
+        };
+
+        playback.range = (start, end);
+        playback.looping = looping.unwrap_or(false);
+        playback.playing = true;
+        let position = start_sec
+            .map(|s| (s * rate).clamp(start, end))
+            .unwrap_or(start);
+        playback.seek(position);
+    }
+
+    player.ensure_output(&app)?;
+    player.status()
+}
+
+#[tauri::command]
+pub fn pause_playback(player: State<'_, PlayerState>) -> Result<PlaybackStatus, String> {
+    player.playback.lock().map_err(|e| e.to_string())?.playing = false;
+    player.status()
+}
+
+#[tauri::command]
+pub fn resume_playback(
+    app: AppHandle,
+    player: State<'_, PlayerState>,
+) -> Result<PlaybackStatus, String> {
+    {
+        let mut playback = player.playback.lock().map_err(|e| e.to_string())?;
+        if playback.track.is_none() {
+            return Err("Belum ada audio yang dimuat".to_string());
+        }
+        // Sudah di ujung range: mulai lagi dari awal
+        if playback.finished() {
+            let start = playback.range.0;
+            playback.seek(start);
+        }
+        playback.playing = true;
+    }
+
+    player.ensure_output(&app)?;
+    player.status()
+}
+
+// Hentikan playback, lepas audio dan output device
+#[tauri::command]
+pub fn stop_playback(player: State<'_, PlayerState>) -> Result<PlaybackStatus, String> {
+    player.stop_output()?;
+    *player.playback.lock().map_err(|e| e.to_string())? = Playback::default();
+    player.status()
+}
+
+// Seek/scrub; posisi dibatasi ke range region yang sedang diputar
+#[tauri::command]
+pub fn seek_playback(
+    player: State<'_, PlayerState>,
+    position_sec: f64,
+) -> Result<PlaybackStatus, String> {
+    {
+        let mut playback = player.playback.lock().map_err(|e| e.to_string())?;
+        let Some(rate) = playback.track.as_ref().map(|t| t.sample_rate as f64) else {
+            return Err("Belum ada audio yang dimuat".to_string());
+        };
+        let (start, end) = playback.range;
+        playback.seek((position_sec * rate).clamp(start, end));
+    }
+
+    player.status()
+}
+
+// Atur loop; start/end opsional untuk loop di luar region (dalam detik)
+#[tauri::command]
+pub fn set_playback_loop(
+    player: State<'_, PlayerState>,
+    looping: bool,
+    start_sec: Option<f64>,
+    end_sec: Option<f64>,
+) -> Result<PlaybackStatus, String> {
+    {
+        let mut playback = player.playback.lock().map_err(|e| e.to_string())?;
+        let Some((rate, frames)) = playback
+            .track
+            .as_ref()
+            .map(|t| (t.sample_rate as f64, t.frames))
+        else {
+            return Err("Belum ada audio yang dimuat".to_string());
+        };
+
+        if start_sec.is_some() || end_sec.is_some() {
+            let start = start_sec
+                .map(|s| s * rate)
+                .unwrap_or(0.0)
+                .clamp(0.0, frames);
+            let end = end_sec
+                .map(|s| s * rate)
+                .unwrap_or(frames)
+                .clamp(start, frames);
+            if end <= start {
+                return Err("Range loop tidak valid".to_string());
+            }
+            playback.range = (start, end);
+        }
+        playback.looping = looping;
+        // Buffer sudah di-decode untuk range/loop lama: mulai ulang dari posisi sekarang
+        let position = playback.position.clamp(playback.range.0, playback.range.1);
+        playback.seek(position);
+    }
+
+    player.status()
+}
+
+#[tauri::command]
+pub fn get_playback_status(player: State<'_, PlayerState>) -> Result<PlaybackStatus, String> {
+    player.status()
+}
@@ -0,0 +1,731 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+};
+
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, RgbaImage};
+
+// Format yang tidak bisa (atau tidak cukup baik) di-decode langsung oleh crate image
+const RAW_EXTENSIONS: &[&str] = &[
+    "cr2", "cr3", "crw", "nef", "nrw", "arw", "srf", "sr2", "dng", "raf", "orf", "rw2", "pef",
+    "srw", "3fr", "iiq", "rwl",
+];
+const HEIF_EXTENSIONS: &[&str] = &["heic", "heif", "avif"];
+
+// Batas satu preview JPEG embedded yang dibaca dari file RAW
+const MAX_RAW_PREVIEW_BYTES: u64 = 64 * 1024 * 1024;
+// Cukup untuk header JPEG (APP1 EXIF/MPF) sampai SOF, untuk membandingkan ukuran preview
+const RAW_PREVIEW_HEADER_BYTES: u64 = 1024 * 1024;
+// Layout tidak dikenal / hanya ada thumbnail kecil: cari JPEG di awal file saja
+const RAW_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+const MIN_RAW_PREVIEW_PIXELS: u64 = 640 * 480;
+// Box meta HEIF/AVIF selalu di awal file
+const HEIF_HEADER_BYTES: u64 = 1024 * 1024;
+// Image resource PSD (termasuk thumbnail embedded) biasanya hanya beberapa KB-MB
+const MAX_PSD_RESOURCES: u64 = 64 * 1024 * 1024;
+// PSB bisa puluhan ribu piksel per sisi; di atas ini composite di-decode dengan melompati
+// baris/kolom (hasilnya hanya untuk thumbnail)
+const MAX_PSD_PIXELS: u64 = 50_000_000;
+
+pub fn is_raw(ext: &str) -> bool {
+    RAW_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+pub fn is_heif(ext: &str) -> bool {
+    HEIF_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+pub fn is_psd(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "psd" | "psb")
+}
+
+pub fn is_exr(ext: &str) -> bool {
+    ext.eq_ignore_ascii_case("exr")
+}
+
+// Dimensi + info teknis yang tidak ada di EXIF
+#[derive(Debug, Default)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: Option<u32>, // Per channel
+    pub layer_count: Option<u32>,
+    pub page_count: Option<u32>,
+}
+
+pub fn image_info(path: &str, ext: &str) -> Result<ImageInfo, String> {
+    if is_psd(ext) {
+        let psd = read_psd(path)?.1;
+        return Ok(ImageInfo {
+            width: psd.width,
+            height: psd.height,
+            bit_depth: Some(psd.depth as u32),
+            layer_count: Some(psd.layer_count),
+            page_count: None,
+        });
+    }
+    if is_heif(ext) {
+        return heif_info(path);
+    }
+    if is_raw(ext) {
+        // Ukuran sensor butuh decoder RAW penuh; pakai ukuran preview embedded
+        let preview = raw_preview(path)?;
+        return Ok(ImageInfo {
+            width: preview.width(),
+            height: preview.height(),
+            ..Default::default()
+        });
+    }
+    if is_exr(ext) {
+        return exr_info(path);
+    }
+
+    let decoder = ImageReader::open(path)
+        .map_err(|e| e.to_string())?
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_decoder()
+        .map_err(|e| e.to_string())?;
+    let (width, height) = decoder.dimensions();
+    let color = decoder.original_color_type();
+
+    let page_count = match ext.to_lowercase().as_str() {
+        "tif" | "tiff" => tiff_page_count(path),
+        _ => None,
+    };
+
+    Ok(ImageInfo {
+        width,
+        height,
+        bit_depth: Some((color.bits_per_pixel() / color.channel_count().max(1) as u16) as u32),
+        layer_count: None,
+        page_count,
+    })
+}
+
+fn tiff_page_count(path: &str) -> Option<u32> {
+    let file = File::open(path).ok()?;
+    let mut decoder = tiff::decoder::Decoder::new(BufReader::new(file)).ok()?;
+
+    let mut pages = 1;
+    while decoder.more_images() && decoder.next_image().is_ok() {
+        pages += 1;
+    }
+    Some(pages)
+}
+
+fn exr_info(path: &str) -> Result<ImageInfo, String> {
+    let meta = exr::meta::MetaData::read_from_file(path, false).map_err(|e| e.to_string())?;
+    let first = meta.headers.first().ok_or("EXR tanpa header")?;
+
+    // Layer = nama part (multi-part) + prefix channel ("diffuse.R" -> "diffuse")
+    let mut layers = HashSet::new();
+    let mut bit_depth = 0;
+    for header in &meta.headers {
+        let part = header
+            .own_attributes
+            .layer_name
+            .as_ref()
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+
+        for channel in &header.channels.list {
+            let name = channel.name.to_string();
+            let prefix = name.rsplit_once('.').map(|(p, _)| p.to_string());
+            layers.insert((part.clone(), prefix.unwrap_or_default()));
+            bit_depth = bit_depth.max(channel.sample_type.bytes_per_sample() as u32 * 8);
+        }
+    }
+
+    Ok(ImageInfo {
+        width: first.layer_size.x() as u32,
+        height: first.layer_size.y() as u32,
+        bit_depth: Some(bit_depth),
+        layer_count: Some(layers.len() as u32),
+        page_count: None,
+    })
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// EXR berisi warna linear (bisa > 1.0); tanpa transfer sRGB thumbnail jadi terlalu gelap
+pub fn decode_exr(path: &str) -> Result<RgbaImage, String> {
+    let img = image::open(path).map_err(|e| e.to_string())?.to_rgba32f();
+
+    let rgba = img
+        .pixels()
+        .flat_map(|p| {
+            let [r, g, b, a] = p.0;
+            [
+                linear_to_srgb(r.max(0.0)),
+                linear_to_srgb(g.max(0.0)),
+                linear_to_srgb(b.max(0.0)),
+                a,
+            ]
+            .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+        })
+        .collect();
+
+    RgbaImage::from_raw(img.width(), img.height(), rgba).ok_or("Buffer EXR tidak valid".to_string())
+}
+
+fn be_u32(bytes: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(pos..pos + 4)?.try_into().ok()?,
+    ))
+}
+
+// Box `ispe` (ukuran) dan `pixi` (bit per channel) dari item properties.
+// File HEIF punya beberapa `ispe` (tile, thumbnail, grid); yang terbesar = gambar utama.
+fn heif_info(path: &str) -> Result<ImageInfo, String> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .map_err(|e| e.to_string())?
+        .take(HEIF_HEADER_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| e.to_string())?;
+
+    let mut info = ImageInfo::default();
+    for pos in 0..bytes.len().saturating_sub(4) {
+        match &bytes[pos..pos + 4] {
+            // type(4) version+flags(4) width(4) height(4)
+            b"ispe" => {
+                let (Some(w), Some(h)) = (be_u32(&bytes, pos + 8), be_u32(&bytes, pos + 12)) else {
+                    continue;
+                };
+                if w as u64 * h as u64 > info.width as u64 * info.height as u64 {
+                    info.width = w;
+                    info.height = h;
+                }
+            }
+            // type(4) version+flags(4) num_channels(1) bits(1)...
+            b"pixi" if info.bit_depth.is_none() => {
+                info.bit_depth = bytes.get(pos + 9).map(|b| *b as u32);
+            }
+            _ => {}
+        }
+    }
+
+    if info.width == 0 || info.height == 0 {
+        return Err("Ukuran HEIF tidak ditemukan".to_string());
+    }
+    Ok(info)
+}
+
+fn read_at(file: &mut File, offset: u64, len: u64) -> Option<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut buf = Vec::new();
+    file.take(len).read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+// Nilai TIFF sesuai byte order header (II = little endian, MM = big endian)
+fn tiff_u16(bytes: &[u8], pos: usize, le: bool) -> Option<u16> {
+    let b: [u8; 2] = bytes.get(pos..pos + 2)?.try_into().ok()?;
+    Some(if le {
+        u16::from_le_bytes(b)
+    } else {
+        u16::from_be_bytes(b)
+    })
+}
+
+fn tiff_u32(bytes: &[u8], pos: usize, le: bool) -> Option<u32> {
+    let b: [u8; 4] = bytes.get(pos..pos + 4)?.try_into().ok()?;
+    Some(if le {
+        u32::from_le_bytes(b)
+    } else {
+        u32::from_be_bytes(b)
+    })
+}
+
+// Range (offset, panjang) preview JPEG dari IFD TIFF: JPEGInterchangeFormat, strip
+// tunggal ber-kompresi JPEG (CR2/DNG), dan tag UNDEFINED berisi JPEG (JpgFromRaw RW2).
+// IFD0, IFD berikutnya, dan SubIFD (NEF/DNG) ikut ditelusuri.
+fn tiff_previews(file: &mut File, ranges: &mut Vec<(u64, u64)>) -> Option<()> {
+    let header = read_at(file, 0, 8)?;
+    let le = match header.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+
+    let mut queue = vec![tiff_u32(&header, 4, le)? as u64];
+    let mut visited = HashSet::new();
+
+    while let Some(ifd) = queue.pop() {
+        if ifd == 0 || visited.len() >= 32 || !visited.insert(ifd) {
+            continue;
+        }
+        let Some(count) = read_at(file, ifd, 2).and_then(|b| tiff_u16(&b, 0, le)) else {
+            continue;
+        };
+        let Some(entries) = read_at(file, ifd + 2, count as u64 * 12 + 4) else {
+            continue;
+        };
+
+        let (mut jpeg, mut jpeg_len, mut strip, mut strip_len) = (None, None, None, None);
+        let mut compression = 0;
+
+        for entry in entries.chunks_exact(12) {
+            let (Some(tag), Some(kind), Some(n)) = (
+                tiff_u16(entry, 0, le),
+                tiff_u16(entry, 2, le),
+                tiff_u32(entry, 4, le),
+            ) else {
+                continue;
+            };
+            // SHORT tunggal ada di 2 byte pertama field value
+            let value = if kind == 3 {
+                tiff_u16(entry, 8, le).map(|v| v as u32)
+            } else {
+                tiff_u32(entry, 8, le)
+            };
+            let Some(value) = value else {
+                continue;
+            };
+
+            match tag {
+                0x0103 => compression = value,
+                0x0111 if n == 1 => strip = Some(value as u64),
+                0x0117 if n == 1 => strip_len = Some(value as u64),
+                0x0201 => jpeg = Some(value as u64),
+                0x0202 => jpeg_len = Some(value as u64),
+                0x014A if n == 1 => queue.push(value as u64),
+                0x014A => {
+                    if let Some(offsets) = read_at(file, value as u64, n.min(16) as u64 * 4) {
+                        queue.extend(
+                            (0..offsets.len() / 4)
+                                .filter_map(|i| tiff_u32(&offsets, i * 4, le))
+                                .map(|o| o as u64),
+                        );
+                    }
+                }
+                _ if kind == 7 && n > 1024 => ranges.push((value as u64, n as u64)),
+                _ => {}
+            }
+        }
+
+        if let (Some(offset), Some(len)) = (jpeg, jpeg_len) {
+            ranges.push((offset, len));
+        }
+        // 6 = JPEG lama, 7 = JPEG (bisa juga data sensor lossless, nanti gagal decode)
+        if let (Some(offset), Some(len), 6 | 7) = (strip, strip_len, compression) {
+            ranges.push((offset, len));
+        }
+        if let Some(next) = tiff_u32(&entries, count as usize * 12, le) {
+            queue.push(next as u64);
+        }
+    }
+    Some(())
+}
+
+// CR3 (ISO-BMFF): box PRVW di dalam uuid, plus sample pertama tiap track
+// (track pertama CR3 = JPEG ukuran penuh)
+fn bmff_previews(
+    file: &mut File,
+    start: u64,
+    end: u64,
+    depth: u32,
+    ranges: &mut Vec<(u64, u64)>,
+) -> Option<()> {
+    let mut pos = start;
+    let mut chunk_offset = None;
+    let mut sample_size = None;
+
+    while pos + 8 <= end {
+        let header = read_at(file, pos, 16)?;
+        let (mut size, kind) = (be_u32(&header, 0)? as u64, header.get(4..8)?);
+        let mut body = pos + 8;
+        if size == 1 {
+            size = u64::from_be_bytes(header.get(8..16)?.try_into().ok()?);
+            body += 8;
+        } else if size == 0 {
+            size = end - pos;
+        }
+        if size < body - pos || pos + size > end {
+            break;
+        }
+        let box_end = pos + size;
+
+        match kind {
+            b"moov" | b"trak" | b"mdia" | b"minf" | b"stbl" if depth < 6 => {
+                bmff_previews(file, body, box_end, depth + 1, ranges);
+            }
+            // version+flags(4) entry_count(4) offset pertama
+            b"stco" => {
+                chunk_offset = read_at(file, body + 8, 4)
+                    .and_then(|b| be_u32(&b, 0))
+                    .map(|o| o as u64)
+            }
+            b"co64" => {
+                chunk_offset = read_at(file, body + 8, 8)
+                    .and_then(|b| Some(u64::from_be_bytes(b.try_into().ok()?)))
+            }
+            // version+flags(4) sample_size(4) sample_count(4) [ukuran per sample]
+            b"stsz" => {
+                sample_size = read_at(file, body + 4, 12).and_then(|b| match be_u32(&b, 0)? {
+                    0 => be_u32(&b, 8).map(|s| s as u64),
+                    s => Some(s as u64),
+                })
+            }
+            // uuid(16) lalu box PRVW berisi header pendek + JPEG
+            b"uuid" if depth == 0 => {
+                let head = read_at(file, body, 128.min(box_end - body))?;
+                if let Some(prvw) = head.windows(4).position(|w| w == b"PRVW") {
+                    if let Some(soi) = head[prvw..]
+                        .windows(3)
+                        .position(|w| w == [0xFF, 0xD8, 0xFF])
+                    {
+                        let offset = body + (prvw + soi) as u64;
+                        ranges.push((offset, box_end - offset));
+                    }
+                }
+            }
+            _ => {}
+        }
+        pos = box_end;
+    }
+
+    if let (Some(offset), Some(size)) = (chunk_offset, sample_size) {
+        ranges.push((offset, size));
+    }
+    Some(())
+}
+
+// JPEG di awal file untuk layout yang tidak dikenali (CRW, preview di makernote ORF).
+// Return (piksel, offset, panjang) seperti kandidat lain.
+fn scan_previews(file: &mut File) -> Vec<(u64, u64, u64)> {
+    let Some(bytes) = read_at(file, 0, RAW_SCAN_BYTES) else {
+        return Vec::new();
+    };
+
+    bytes
+        .windows(3)
+        .enumerate()
+        .filter(|(_, w)| w == &[0xFF, 0xD8, 0xFF])
+        .filter_map(|(pos, _)| {
+            let (w, h) = ImageReader::with_format(Cursor::new(&bytes[pos..]), ImageFormat::Jpeg)
+                .into_dimensions()
+                .ok()?;
+            Some((w as u64 * h as u64, pos as u64, MAX_RAW_PREVIEW_BYTES))
+        })
+        .collect()
+}
+
+// Hampir semua RAW (CR2/CR3/NEF/ARW/DNG/RAF/...) menyimpan preview JPEG ukuran penuh
+// atau medium. Lokasinya dibaca dari struktur file (IFD TIFF, box CR3, header RAF),
+// lalu hanya range preview yang dibaca dan yang terbesar di-decode.
+fn raw_preview(path: &str) -> Result<DynamicImage, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let header = read_at(&mut file, 0, 92).unwrap_or_default();
+
+    let mut ranges = Vec::new();
+    if header.starts_with(b"FUJIFILMCCD-RAW") {
+        // RAF: offset + panjang JPEG (big endian) di byte 84
+        if let (Some(offset), Some(len)) = (be_u32(&header, 84), be_u32(&header, 88)) {
+            ranges.push((offset as u64, len as u64));
+        }
+    } else if header.get(4..8) == Some(b"ftyp") {
+        bmff_previews(&mut file, 0, size, 0, &mut ranges);
+    } else {
+        tiff_previews(&mut file, &mut ranges);
+    }
+
+    // Header JPEG saja untuk membandingkan ukuran sebelum decode penuh
+    let mut candidates: Vec<(u64, u64, u64)> = ranges
+        .iter()
+        .filter(|(offset, len)| *offset < size && *len > 0)
+        .filter_map(|&(offset, len)| {
+            let len = len.min(size - offset).min(MAX_RAW_PREVIEW_BYTES);
+            let head = read_at(&mut file, offset, len.min(RAW_PREVIEW_HEADER_BYTES))?;
+            if !head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+                return None;
+            }
+            let (w, h) = ImageReader::with_format(Cursor::new(&head), ImageFormat::Jpeg)
+                .into_dimensions()
+                .ok()?;
+            Some((w as u64 * h as u64, offset, len))
+        })
+        .collect();
+    if candidates
+        .iter()
+        .all(|(pixels, _, _)| *pixels < MIN_RAW_PREVIEW_PIXELS)
+    {
+        candidates.extend(scan_previews(&mut file));
+    }
+    candidates.sort_unstable_by(|a, b| b.cmp(a));
+    candidates.dedup_by_key(|(_, offset, _)| *offset);
+
+    // Data sensor lossless-JPEG tidak bisa di-decode, lanjut ke kandidat berikutnya
+    candidates
+        .into_iter()
+        .find_map(|(_, offset, len)| {
+            let bytes = read_at(&mut file, offset, len)?;
+            image::load_from_memory_with_format(&bytes, ImageFormat::Jpeg).ok()
+        })
+        .ok_or("Preview JPEG tidak ditemukan di file RAW".to_string())
+}
+
+// Preview RAW jarang ikut diputar; orientasi diambil dari EXIF file RAW-nya
+pub fn decode_raw_preview(path: &str, orientation: Option<u32>) -> Result<RgbaImage, String> {
+    let mut img = raw_preview(path)?;
+    if let Some(orientation) =
+        orientation.and_then(|o| image::metadata::Orientation::from_exif(o as u8))
+    {
+        img.apply_orientation(orientation);
+    }
+    Ok(img.to_rgba8())
+}
+
+struct Psd {
+    version: u16, // 1 = PSD, 2 = PSB (field panjang 8 byte)
+    channels: u16,
+    width: u32,
+    height: u32,
+    depth: u16,
+    color_mode: u16, // 1 grayscale, 3 RGB, 4 CMYK, ...
+    layer_count: u32,
+    merged_alpha: bool, // Channel ekstra pertama = transparansi gambar gabungan
+    thumbnail: Option<Vec<u8>>,
+}
+
+fn read_be<R: Read>(r: &mut R, len: usize) -> Result<u64, String> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf[8 - len..])
+        .map_err(|e| format!("PSD terpotong: {}", e))?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn skip<R: Seek>(r: &mut R, len: u64) -> Result<(), String> {
+    r.seek(std::io::SeekFrom::Current(len as i64))
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+// Thumbnail JPEG dari image resource 1036 (header 28 byte sebelum data JPEG)
+fn psd_thumbnail(resources: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+    // Resource block: "8BIM" id(2) nama(pascal, genap) size(4) data(genap)
+    while pos + 12 <= resources.len() && &resources[pos..pos + 4] == b"8BIM" {
+        let id = u16::from_be_bytes([resources[pos + 4], resources[pos + 5]]);
+        let name_total = (resources[pos + 6] as usize + 2) & !1;
+        let size_pos = pos + 6 + name_total;
+        let size = be_u32(resources, size_pos)? as usize;
+        let data_start = size_pos + 4;
+
+        if id == 0x040C {
+            let data = resources.get(data_start..data_start + size)?;
+            return (be_u32(data, 0) == Some(1)).then(|| data.get(28..).map(|d| d.to_vec()))?;
+        }
+        pos = data_start + ((size + 1) & !1);
+    }
+    None
+}
+
+// Baca header + jumlah layer. Reader dikembalikan tepat di awal section image data.
+fn read_psd(path: &str) -> Result<(BufReader<File>, Psd), String> {
+    let mut r = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+
+    let mut signature = [0u8; 4];
+    r.read_exact(&mut signature).map_err(|e| e.to_string())?;
+    if &signature != b"8BPS" {
+        return Err("Bukan file PSD".to_string());
+    }
+
+    let version = read_be(&mut r, 2)? as u16;
+    if version != 1 && version != 2 {
+        return Err(format!("Versi PSD tidak dikenal: {}", version));
+    }
+    skip(&mut r, 6)?;
+
+    let mut psd = Psd {
+        version,
+        channels: read_be(&mut r, 2)? as u16,
+        height: read_be(&mut r, 4)? as u32,
+        width: read_be(&mut r, 4)? as u32,
+        depth: read_be(&mut r, 2)? as u16,
+        color_mode: read_be(&mut r, 2)? as u16,
+        layer_count: 0,
+        merged_alpha: false,
+        thumbnail: None,
+    };
+    let long_len = if version == 2 { 8 } else { 4 };
+
+    // Color mode data
+    let len = read_be(&mut r, 4)?;
+    skip(&mut r, len)?;
+
+    // Image resources
+    let len = read_be(&mut r, 4)?;
+    if len <= MAX_PSD_RESOURCES {
+        let mut resources = vec![0u8; len as usize];
+        r.read_exact(&mut resources).map_err(|e| e.to_string())?;
+        psd.thumbnail = psd_thumbnail(&resources);
+    } else {
+        skip(&mut r, len)?;
+    }
+
+    // Layer and mask info: panjang layer info lalu jumlah layer (i16, negatif =
+    // channel alpha pertama berisi transparansi gambar gabungan)
+    let len = read_be(&mut r, long_len)?;
+    let section_start = r.stream_position().map_err(|e| e.to_string())?;
+    if len > 0 {
+        let layer_info_len = read_be(&mut r, long_len)?;
+        if layer_info_len > 0 {
+            let count = read_be(&mut r, 2)? as u16 as i16;
+            psd.layer_count = count.unsigned_abs() as u32;
+            psd.merged_alpha = count < 0;
+        }
+    }
+    r.seek(std::io::SeekFrom::Start(section_start + len))
+        .map_err(|e| e.to_string())?;
+
+    Ok((r, psd))
+}
+
+// PackBits: header n >= 0 -> salin n+1 byte, n < 0 -> ulang byte berikutnya 1-n kali
+fn unpack_bits(src: &[u8], dst: &mut [u8]) {
+    let (mut i, mut o) = (0, 0);
+    while i < src.len() && o < dst.len() {
+        let n = src[i] as i8;
+        i += 1;
+        if n >= 0 {
+            let count = (n as usize + 1).min(src.len() - i).min(dst.len() - o);
+            dst[o..o + count].copy_from_slice(&src[i..i + count]);
+            i += count;
+            o += count;
+        } else if n != -128 {
+            let count = ((1 - n as isize) as usize).min(dst.len() - o);
+            if let Some(value) = src.get(i) {
+                dst[o..o + count].fill(*value);
+            }
+            i += 1;
+            o += count;
+        }
+    }
+}
+
+// Gambar gabungan (composite) dari section image data: channel planar, raw atau RLE.
+// Dibaca per baris; gambar raksasa (PSB) diambil tiap `step` baris/kolom saja.
+fn psd_composite(r: &mut BufReader<File>, psd: &Psd) -> Result<RgbaImage, String> {
+    let base_channels: u16 = match psd.color_mode {
+        1 => 1,
+        3 => 3,
+        4 => 4,
+        mode => return Err(format!("Color mode PSD {} tidak didukung", mode)),
+    };
+    if psd.depth != 8 && psd.depth != 16 {
+        return Err(format!("Bit depth PSD {} tidak didukung", psd.depth));
+    }
+    if psd.channels < base_channels {
+        return Err("Jumlah channel PSD tidak cocok dengan color mode".to_string());
+    }
+    let alpha = psd.merged_alpha && psd.channels > base_channels;
+    let wanted = (base_channels + alpha as u16).min(psd.channels) as usize;
+
+    let (width, height) = (psd.width as usize, psd.height as usize);
+    let bytes_per_sample = psd.depth as usize / 8;
+    let row_len = width * bytes_per_sample;
+
+    let pixels = width as u64 * height as u64;
+    let step = (pixels as f64 / MAX_PSD_PIXELS as f64)
+        .sqrt()
+        .ceil()
+        .max(1.0) as usize;
+    let (out_width, out_height) = (width.div_ceil(step), height.div_ceil(step));
+
+    // 16-bit big endian: byte pertama = 8 bit teratas
+    let mut planes = vec![vec![0u8; out_width * out_height]; wanted];
+    let keep_row = |plane: &mut [u8], y: usize, row: &[u8]| {
+        let out_row = &mut plane[(y / step) * out_width..(y / step + 1) * out_width];
+        for (x, out) in out_row.iter_mut().enumerate() {
+            *out = row[x * step * bytes_per_sample];
+        }
+    };
+    let skip_bytes = |r: &mut BufReader<File>, len: usize| {
+        r.seek_relative(len as i64).map_err(|e| e.to_string())
+    };
+
+    let compression = read_be(r, 2)?;
+    let mut row = vec![0u8; row_len];
+
+    match compression {
+        0 => {
+            for plane in planes.iter_mut() {
+                for y in 0..height {
+                    if y % step != 0 {
+                        skip_bytes(r, row_len)?;
+                        continue;
+                    }
+                    r.read_exact(&mut row).map_err(|e| e.to_string())?;
+                    keep_row(plane, y, &row);
+                }
+            }
+        }
+        1 => {
+            // Byte count tiap baris untuk SEMUA channel, baru data RLE
+            let count_len = if psd.version == 2 { 4 } else { 2 };
+            let mut counts = Vec::with_capacity(psd.channels as usize * height);
+            for _ in 0..psd.channels as usize * height {
+                counts.push(read_be(r, count_len)? as usize);
+            }
+
+            let mut packed = Vec::new();
+            for (channel, plane) in planes.iter_mut().enumerate() {
+                for (y, count) in counts[channel * height..(channel + 1) * height]
+                    .iter()
+                    .enumerate()
+                {
+                    if y % step != 0 {
+                        skip_bytes(r, *count)?;
+                        continue;
+                    }
+                    packed.resize(*count, 0);
+                    r.read_exact(&mut packed).map_err(|e| e.to_string())?;
+                    unpack_bits(&packed, &mut row);
+                    keep_row(plane, y, &row);
+                }
+            }
+        }
+        other => return Err(format!("Kompresi PSD {} tidak didukung", other)),
+    }
+
+    let sample = |channel: usize, i: usize| planes[channel][i];
+
+    let mut img = RgbaImage::new(out_width as u32, out_height as u32);
+    for (i, pixel) in img.pixels_mut().enumerate() {
+        let [r, g, b] = match psd.color_mode {
+            1 => [sample(0, i); 3],
+            3 => [sample(0, i), sample(1, i), sample(2, i)],
+            // CMYK PSD disimpan terbalik (255 = tanpa tinta)
+            _ => {
+                let k = sample(3, i) as u32;
+                [0, 1, 2].map(|c| (sample(c, i) as u32 * k / 255) as u8)
+            }
+        };
+        let a = if alpha { sample(wanted - 1, i) } else { 255 };
+        pixel.0 = [r, g, b, a];
+    }
+
+    Ok(img)
+}
+
+// Composite dulu; file tanpa "maximize compatibility"/mode eksotis fallback ke thumbnail embedded
+pub fn decode_psd(path: &str) -> Result<RgbaImage, String> {
+    let (mut r, psd) = read_psd(path)?;
+
+    psd_composite(&mut r, &psd).or_else(|e| {
+        let jpeg = psd.thumbnail.as_ref().ok_or(e)?;
+        image::load_from_memory_with_format(jpeg, ImageFormat::Jpeg)
+            .map(|img| img.to_rgba8())
+            .map_err(|e| e.to_string())
+    })
+}
@@ -0,0 +1,440 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rusqlite::{Connection, OptionalExtension};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{
+    models::{AssetMetadata, PlayablePath, ProgressEvent},
+    thumb_cache_lib::content_key,
+    utils::run_ffmpeg,
+    DbState,
+};
+
+// Di atas batas ini file tetap dibuatkan proxy walaupun formatnya bisa diputar webview
+const AUDIO_PROXY_MIN_SIZE: u64 = 200 * 1024 * 1024;
+const VIDEO_PROXY_MIN_SIZE: u64 = 500 * 1024 * 1024;
+const VIDEO_PROXY_MAX_HEIGHT: u32 = 1080;
+
+// Format yang bisa langsung diputar webview
+const PLAYABLE_AUDIO: &[&str] = &["mp3", "wav", "ogg", "flac", "aac", "m4a"];
+const PLAYABLE_VIDEO: &[&str] = &["mp4", "webm"];
+const PROXY_EXTENSIONS: &[&str] = &["mp3", "mp4"];
+// Encode yang sedang jalan belum tercatat di DB; file yang masih ditulis tidak di-GC
+const PROXY_GC_GRACE: Duration = Duration::from_secs(10 * 60);
+
+// Sidik jari file sumber (ukuran + waktu modifikasi) untuk invalidasi proxy
+fn source_fingerprint(path: &str) -> Option<(i64, i64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    let modified = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some((meta.len() as i64, modified as i64))
+}
+
+fn needs_proxy(asset_type: &str, extension: &str, file_size: u64, metadata: Option<&str>) -> bool {
+    let ext = extension.to_lowercase();
+    let metadata: Option<AssetMetadata> = metadata.and_then(|m| serde_json::from_str(m).ok());
+
+    match asset_type {
+        "audio" => {
+            let heavy = match metadata {
+                Some(AssetMetadata::Audio(audio)) => {
+                    audio.sample_rate > 48000 || audio.channels.unwrap_or(2) > 2
+                }
+                _ => false,
+            };
+            !PLAYABLE_AUDIO.contains(&ext.as_str()) || heavy || file_size > AUDIO_PROXY_MIN_SIZE
+        }
+        "video" => {
+            let heavy = match metadata {
+                Some(AssetMetadata::Video(video)) => video.height > VIDEO_PROXY_MAX_HEIGHT,
+                _ => false,
+            };
+            !PLAYABLE_VIDEO.contains(&ext.as_str()) || heavy || file_size > VIDEO_PROXY_MIN_SIZE
+        }
+        _ => false,
+    }
+}
+
+pub fn proxy_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("proxies");
+
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    Ok(dir)
+}
+
+// Audio: MP3 stereo 48 kHz. Video: H.264 540p + AAC stereo, faststart untuk streaming.
+fn proxy_args(asset_type: &str, input: &str, output: &Path) -> Vec<String> {
+    let mut args = vec!["-i".to_string(), input.to_string()];
+
+    let codec_args: &[&str] = if asset_type == "video" {
+        &[
+            "-vf",
+            "scale=-2:'min(540,ih)'",
+            "-c:v",
+            "libx264",
+            "-preset",
+            "veryfast",
+            "-crf",
+            "28",
+            "-pix_fmt",
+            "yuv420p",
+            "-c:a",
+            "aac",
+            "-b:a",
+            "128k",
+            "-ac",
+            "2",
+            "-movflags",
+            "+faststart",
+        ]
+    } else {
+        &[
+            "-vn",
+            "-c:a",
+            "libmp3lame",
+            "-b:a",
+            "192k",
+            "-ac",
+            "2",
+            "-ar",
+            "48000",
+        ]
+    };
+
+    args.extend(codec_args.iter().map(|s| s.to_string()));
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+// Nama file proxy diambil dari isi sumber (bukan id asset yang bisa dipakai ulang
+// setelah clear_db); asset dengan isi sama berbagi satu file
+fn proxy_file_name(key: &str, asset_type: &str) -> String {
+    let ext = if asset_type == "video" { "mp4" } else { "mp3" };
+    format!("{}.{}", key, ext)
+}
+
+fn proxy_in_use(conn: &Connection, proxy_path: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT 1 FROM proxies WHERE proxy_path = ?1",
+        [proxy_path],
+        |_| Ok(()),
+    )
+    .optional()
+    .map(|r| r.is_some())
+    .map_err(|e| e.to_string())
+}
+
+// Hapus proxy milik asset; file-nya ikut dihapus kalau tidak dipakai asset lain
+pub fn remove_proxy(conn: &Connection, asset_id: i64) -> Result<(), String> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT proxy_path FROM proxies WHERE asset_id = ?1",
+            [asset_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(path) = existing {
+        conn.execute("DELETE FROM proxies WHERE asset_id = ?1", [asset_id])
+            .map_err(|e| e.to_string())?;
+        if !proxy_in_use(conn, &path)? {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
+
+// Hapus file proxy yang tidak dirujuk baris mana pun (asset terhapus lewat cascade,
+// clear_db, atau proxy lama berbasis id). File lain di folder proxy tidak disentuh.
+pub fn collect_proxy_garbage(dir: &Path, conn: &Connection) -> Result<usize, String> {
+    let mut stmt = conn
+        .prepare("SELECT proxy_path FROM proxies")
+        .map_err(|e| e.to_string())?;
+    let referenced: HashSet<PathBuf> = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .map(PathBuf::from)
+        .collect();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(0);
+    };
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_proxy = path
+            .extension()
+            .map(|e| PROXY_EXTENSIONS.contains(&e.to_string_lossy().as_ref()))
+            .unwrap_or(false);
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        let recent = meta
+            .modified()
+            .ok()
+            .and_then(|m| now.duration_since(m).ok())
+            .map(|age| age < PROXY_GC_GRACE)
+            .unwrap_or(true);
+
+        if !is_proxy || !meta.is_file() || recent || referenced.contains(&path) {
+            continue;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+// Path proxy yang masih valid; proxy basi (sumber berubah/file hilang) langsung dibuang
+fn valid_proxy(
+    conn: &Connection,
+    asset_id: i64,
+    source_path: &str,
+) -> Result<Option<String>, String> {
+    let row: Option<(String, i64, i64)> = conn
+        .query_row(
+            "SELECT proxy_path, source_size, source_mtime FROM proxies WHERE asset_id = ?1",
+            [asset_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let Some((proxy_path, size, mtime)) = row else {
+        return Ok(None);
+    };
+
+    if Path::new(&proxy_path).exists() && source_fingerprint(source_path) == Some((size, mtime)) {
+        return Ok(Some(proxy_path));
+    }
+
+    remove_proxy(conn, asset_id)?;
+    Ok(None)
+}
+
+// (asset id, path, filename, tipe)
+type ProxyJob = (i64, String, String, String);
+
+// asset_ids kosong = semua asset audio/video yang butuh proxy
+#[tauri::command]
+pub fn generate_proxies(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Option<Vec<i64>>,
+    overwrite: bool,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let dir = proxy_dir(&app)?;
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<ProxyJob> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        collect_proxy_garbage(&dir, &conn)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, original_path, filename, type, extension, file_size, metadata
+                 FROM assets WHERE type IN ('audio', 'video')",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows: Vec<(i64, String, String, String, bool)> = stmt
+            .query_map([], |row| {
+                let asset_type: String = row.get(3)?;
+                let metadata: Option<String> = row.get(6)?;
+                let needed = needs_proxy(
+                    &asset_type,
+                    &row.get::<_, String>(4)?,
+                    row.get::<_, i64>(5)? as u64,
+                    metadata.as_deref(),
+                );
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, asset_type, needed))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut selected = Vec::new();
+        for (id, path, filename, asset_type, needed) in rows {
+            let wanted = match &asset_ids {
+                Some(ids) => ids.contains(&id),
+                None => needed,
+            };
+            if !wanted {
+                continue;
+            }
+
+            if overwrite {
+                remove_proxy(&conn, id)?;
+            } else if valid_proxy(&conn, id, &path)?.is_some() {
+                continue;
+            }
+            selected.push((id, path, filename, asset_type));
+        }
+        selected
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Semua proxy sudah up to date.".to_string());
+    }
+
+    let processed_count = std::sync::Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        // Asset dengan isi sama cukup di-encode sekali (dan tidak menulis file yang sama bersamaan)
+        let mut groups: HashMap<String, Vec<&ProxyJob>> = HashMap::new();
+        for item in &to_process {
+            match content_key(&item.1) {
+                Ok(key) => groups
+                    .entry(proxy_file_name(&key, &item.3))
+                    .or_default()
+                    .push(item),
+                Err(e) => println!("Gagal membaca {}: {}", item.2, e),
+            }
+        }
+        let groups: Vec<(String, Vec<&ProxyJob>)> = groups.into_iter().collect();
+
+        groups.par_iter().for_each(|(file_name, items)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let (_, path, filename, asset_type) = items[0];
+            let current = processed_count.fetch_add(items.len(), Ordering::SeqCst) + items.len();
+
+            let _ = app.emit(
+                "proxy-progress",
+                ProgressEvent {
+                    name: "Proxy".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let out_path = dir.join(file_name);
+            let out_str = out_path.to_string_lossy().to_string();
+            // Ambil sidik jari sebelum encode supaya perubahan selama proses ikut terdeteksi
+            let fingerprints: Vec<Option<(i64, i64)>> = items
+                .iter()
+                .map(|item| source_fingerprint(&item.1))
+                .collect();
+
+            // Proxy dengan isi sama sudah dipakai asset lain: tinggal dirujuk
+            let shared = !overwrite
+                && out_path.exists()
+                && db_arc
+                    .lock()
+                    .map(|conn| proxy_in_use(&conn, &out_str).unwrap_or(false))
+                    .unwrap_or(false);
+
+            let result = if shared {
+                Ok(())
+            } else {
+                run_ffmpeg(&app, &proxy_args(asset_type, path, &out_path))
+            };
+            let Ok(conn) = db_arc.lock() else {
+                return;
+            };
+            if let Err(e) = result {
+                if !proxy_in_use(&conn, &out_str).unwrap_or(true) {
+                    let _ = std::fs::remove_file(&out_path);
+                }
+                println!("Gagal membuat proxy untuk {}: {}", filename, e);
+                return;
+            }
+
+            for (item, fingerprint) in items.iter().zip(fingerprints) {
+                if let Some((size, mtime)) = fingerprint {
+                    let _ = conn.execute(
+                        "INSERT OR REPLACE INTO proxies
+                         (asset_id, proxy_path, source_size, source_mtime)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        rusqlite::params![item.0, out_str, size, mtime],
+                    );
+                }
+            }
+            // Sumber berubah selama encode: file tidak dirujuk siapa pun, buang
+            if !proxy_in_use(&conn, &out_str).unwrap_or(true) {
+                let _ = std::fs::remove_file(&out_path);
+            }
+        });
+
+        let _ = app.emit(
+            "proxy-progress",
+            ProgressEvent {
+                name: "Proxy".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai pembuatan proxy untuk {} file...",
+        total_files
+    ))
+}
+
+// Path terbaik untuk diputar frontend: proxy kalau ada dan masih valid, selain itu file asli
+#[tauri::command]
+pub fn get_playable_path(state: State<'_, DbState>, asset_id: i64) -> Result<PlayablePath, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let (path, asset_type, ext, size, metadata): (String, String, String, i64, Option<String>) =
+        conn.query_row(
+            "SELECT original_path, type, extension, file_size, metadata FROM assets WHERE id = ?1",
+            [asset_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Asset tidak ditemukan: {}", e))?;
+
+    if let Some(proxy_path) = valid_proxy(&conn, asset_id, &path)? {
+        return Ok(PlayablePath {
+            path: proxy_path,
+            is_proxy: true,
+            needs_proxy: false,
+        });
+    }
+
+    Ok(PlayablePath {
+        needs_proxy: needs_proxy(&asset_type, &ext, size as u64, metadata.as_deref()),
+        path,
+        is_proxy: false,
+    })
+}
@@ -1,29 +1,90 @@
 use std::{
     fs::File,
-    path::Path,
-    sync::atomic::{AtomicUsize, Ordering},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rusqlite::{Connection, ToSql};
 use symphonia::core::audio::SampleBuffer;
-use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
 use symphonia::core::errors::Error;
 use symphonia::core::formats::FormatOptions;
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
+use symphonia::core::sample::SampleFormat;
 use symphonia::default::get_probe;
 use tauri::{AppHandle, Emitter, State};
 
-use crate::{models::ProgressEvent, DbState};
+use crate::{
+    audio_meta_lib::{audio_search_text, read_audio_metadata},
+    feature_lib::update_audio_features,
+    models::{AssetMetadata, PaginatedResponse, ProgressEvent, Region},
+    utils::{run_ffmpeg, run_ffmpeg_stream},
+    video_lib::probe_video,
+    DbState,
+};
 
-pub fn get_audio_waveform(
+// Hasil decode lengkap satu file audio (sample interleaved, -1 s/d 1)
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub bits_per_sample: Option<u32>,
+    pub is_float: bool,
+    // Panjang total dari header, tetap valid walau decode dibatasi
+    pub total_frames: Option<u64>,
+}
+
+impl DecodedAudio {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1)
+    }
+
+    pub fn duration_sec(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+        self.frames() as f64 / self.sample_rate as f64
+    }
+
+    pub fn total_duration_sec(&self) -> f64 {
+        match self.total_frames {
+            Some(frames) if self.sample_rate > 0 => frames as f64 / self.sample_rate as f64,
+            _ => self.duration_sec(),
+        }
+    }
+
+    pub fn to_mono(&self) -> Vec<f32> {
+        let channels = self.channels as f32;
+        self.samples
+            .chunks(self.channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels)
+            .collect()
+    }
+}
+
+pub fn decode_audio(path: &str) -> Result<DecodedAudio, Box<dyn std::error::Error>> {
+    decode_audio_limited(path, None)
+}
+
+// Sama seperti decode_audio tapi berhenti setelah max_sec detik (untuk analisis file panjang)
+pub fn decode_audio_limited(
     path: &str,
-    num_bars: usize,
-) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+    max_sec: Option<f64>,
+) -> Result<DecodedAudio, Box<dyn std::error::Error>> {
     let src = File::open(Path::new(path))?;
     let mss = MediaSourceStream::new(Box::new(src), Default::default());
-    let hint = Hint::new();
+
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(path).extension() {
+        hint.with_extension(&ext.to_string_lossy());
+    }
+
     let probed = get_probe().format(
         &hint,
         mss,
@@ -34,12 +95,140 @@ pub fn get_audio_waveform(
     let track = format.default_track().ok_or("No default track")?;
     let track_id = track.id;
 
+    let mut sample_rate = track.codec_params.sample_rate.unwrap_or(0);
+    let mut channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(0);
+    let bits_per_sample = track.codec_params.bits_per_sample;
+    let is_float = matches!(
+        track.codec_params.sample_format,
+        Some(SampleFormat::F32) | Some(SampleFormat::F64)
+    );
+    let total_frames = track.codec_params.n_frames;
+
     let mut decoder =
         symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
 
-    // Kita kumpulkan peak dari setiap paket audio (Streaming)
+    let mut samples: Vec<f32> = Vec::new();
+
+    while let Ok(packet) = format.next_packet() {
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = spec.rate;
+                channels = spec.channels.count();
+
+                let mut sample_buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                sample_buf.copy_interleaved_ref(decoded);
+                samples.extend_from_slice(sample_buf.samples());
+
+                if let Some(max_sec) = max_sec {
+                    if samples.len() as f64 >= max_sec * sample_rate as f64 * channels as f64 {
+                        break;
+                    }
+                }
+            }
+            // Paket rusak dilewati saja, bukan akhir file
+            Err(Error::DecodeError(_)) => continue,
+            Err(_) => break,
+        }
+    }
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+        channels: channels.max(1),
+        bits_per_sample,
+        is_float,
+        total_frames,
+    })
+}
+
+// Cari sample pertama & terakhir yang melewati threshold (dalam dBFS).
+// Return None kalau seluruh file di bawah threshold.
+pub fn detect_silence_bounds(audio: &DecodedAudio, threshold_db: f32) -> Option<(f64, f64)> {
+    if audio.sample_rate == 0 {
+        return None;
+    }
+
+    let threshold = 10f32.powf(threshold_db / 20.0);
+    let is_loud = |frame: &[f32]| frame.iter().any(|s| s.abs() > threshold);
+
+    let first = audio.samples.chunks(audio.channels).position(is_loud)?;
+    let last = audio.samples.chunks(audio.channels).rposition(is_loud)?;
+
+    let rate = audio.sample_rate as f64;
+    Some((first as f64 / rate, (last + 1) as f64 / rate))
+}
+
+// Ringkasan satu paket/blok audio: peak untuk bentuk waveform, energi untuk RMS
+#[derive(Clone, Copy, Default)]
+struct WaveBlock {
+    peak: f32,
+    sum_sq: f64,
+    len: usize,
+}
+
+impl WaveBlock {
+    fn push(&mut self, sample: f32) {
+        self.peak = self.peak.max(sample.abs());
+        self.sum_sq += (sample * sample) as f64;
+        self.len += 1;
+    }
+}
+
+// Waveform per bar: peak absolut + RMS di rentang yang sama. Peak saja tidak
+// membedakan transient pendek dari dialog/aksi yang padat; RMS menunjukkan loudness-nya.
+pub struct Waveform {
+    pub peaks: Vec<f32>,
+    pub rms: Vec<f32>,
+}
+
+impl Waveform {
+    fn flat(num_bars: usize) -> Self {
+        Waveform {
+            peaks: vec![0.0; num_bars],
+            rms: vec![0.0; num_bars],
+        }
+    }
+}
+
+pub fn get_audio_waveform(
+    path: &str,
+    num_bars: usize,
+) -> Result<Waveform, Box<dyn std::error::Error>> {
+    let src = File::open(Path::new(path))?;
+    let mss = MediaSourceStream::new(Box::new(src), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = Path::new(path).extension() {
+        hint.with_extension(&ext.to_string_lossy());
+    }
+    let probed = get_probe().format(
+        &hint,
+        mss,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut format = probed.format;
+
+    // Track pertama yang bisa di-decode (di file video, track video dilewati)
+    let (track_id, mut decoder) = format
+        .tracks()
+        .iter()
+        .filter(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .find_map(|t| {
+            symphonia::default::get_codecs()
+                .make(&t.codec_params, &DecoderOptions::default())
+                .ok()
+                .map(|decoder| (t.id, decoder))
+        })
+        .ok_or("No decodable audio track")?;
+
+    // Kita kumpulkan peak + energi dari setiap paket audio (Streaming)
     // Ini JAUH lebih hemat RAM daripada menyimpan semua sample
-    let mut packet_peaks: Vec<f32> = Vec::new();
+    let mut packet_blocks: Vec<WaveBlock> = Vec::new();
 
     loop {
         let packet = match format.next_packet() {
@@ -62,43 +251,180 @@ pub fn get_audio_waveform(
                 sample_buf.copy_interleaved_ref(decoded);
                 let samples = sample_buf.samples();
 
-                // Cari peak di paket ini saja
-                let mut p_max = 0.0f32;
+                // Ringkas paket ini saja
+                let mut block = WaveBlock::default();
                 for &s in samples {
-                    let abs_s = s.abs();
-                    if abs_s > p_max {
-                        p_max = abs_s;
-                    }
+                    block.push(s);
                 }
-                packet_peaks.push(p_max);
+                packet_blocks.push(block);
             }
             Err(_) => break,
         }
     }
 
-    if packet_peaks.is_empty() {
-        return Ok(vec![0.0; num_bars]);
+    if packet_blocks.is_empty() {
+        return Ok(Waveform::flat(num_bars));
     }
 
-    // 2. Resample packet_peaks menjadi tepat num_bars (thumbnail size)
-    let chunk_size = (packet_peaks.len() as f32 / num_bars as f32).max(1.0);
-    let mut waveform = Vec::with_capacity(num_bars);
+    Ok(blocks_to_bars(&packet_blocks, num_bars))
+    // Kecepatan Maksimal: Ini adalah jalur eksekusi paling pendek. Decoding -> Ringkasan Paket -> Resample -> Selesai.
+}
+
+// Resample deretan blok menjadi tepat num_bars (thumbnail size): peak = max,
+// RMS dihitung ulang dari total energi supaya paket pendek tidak berbobot berlebih
+fn blocks_to_bars(blocks: &[WaveBlock], num_bars: usize) -> Waveform {
+    let chunk_size = (blocks.len() as f32 / num_bars as f32).max(1.0);
+    let mut waveform = Waveform {
+        peaks: Vec::with_capacity(num_bars),
+        rms: Vec::with_capacity(num_bars),
+    };
 
     for i in 0..num_bars {
         let start = (i as f32 * chunk_size) as usize;
         let end = ((i + 1) as f32 * chunk_size) as usize;
 
-        let mut peak = 0.0f32;
-        for j in start..end.min(packet_peaks.len()) {
-            if packet_peaks[j] > peak {
-                peak = packet_peaks[j];
+        let bar = blocks
+            .get(start.min(blocks.len())..end.min(blocks.len()))
+            .unwrap_or_default()
+            .iter()
+            .fold(WaveBlock::default(), |acc, b| WaveBlock {
+                peak: acc.peak.max(b.peak),
+                sum_sq: acc.sum_sq + b.sum_sq,
+                len: acc.len + b.len,
+            });
+
+        waveform.peaks.push(bar.peak);
+        waveform.rms.push(if bar.len > 0 {
+            (bar.sum_sq / bar.len as f64).sqrt() as f32
+        } else {
+            0.0
+        });
+    }
+
+    waveform
+}
+
+// Fallback lewat ffmpeg untuk container/codec yang tidak didukung symphonia
+// (mis. AC-3/E-AC-3 di MOV/MKV). Audio di-downmix mono 8 kHz, cukup untuk peak & RMS.
+// PCM dibaca per blok dari pipe dan langsung dilipat jadi ringkasan blok.
+fn get_waveform_ffmpeg(app: &AppHandle, path: &str, num_bars: usize) -> Result<Waveform, String> {
+    let args: Vec<String> = [
+        "-i", path, "-vn", "-map", "0:a:0", "-ac", "1", "-ar", "8000", "-f", "f32le", "pipe:1",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    // Blok 1024 sample, setara satu paket audio di jalur symphonia
+    let mut blocks: Vec<WaveBlock> = Vec::new();
+    let mut block = WaveBlock::default();
+    // Sisa byte yang belum genap satu sample f32
+    let mut pending: Vec<u8> = Vec::new();
+
+    run_ffmpeg_stream(app, &args, |bytes| {
+        pending.extend_from_slice(bytes);
+        let whole = pending.len() / 4 * 4;
+
+        for b in pending[..whole].chunks_exact(4) {
+            block.push(f32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+            if block.len == 1024 {
+                blocks.push(block);
+                block = WaveBlock::default();
             }
         }
-        waveform.push(peak);
+        pending.drain(..whole);
+    })?;
+
+    if block.len > 0 {
+        blocks.push(block);
+    }
+    if blocks.is_empty() {
+        return Err("Tidak ada stream audio".to_string());
     }
 
-    Ok(waveform)
-    // Kecepatan Maksimal: Ini adalah jalur eksekusi paling pendek. Decoding -> Peak Paket -> Resample -> Selesai.
+    Ok(blocks_to_bars(&blocks, num_bars))
+}
+
+// Waveform untuk file video: symphonia dulu (demux MP4/MOV/MKV), fallback ke ffmpeg.
+// Video tanpa stream audio dapat waveform datar supaya tidak dicoba ulang terus.
+pub fn get_video_waveform(
+    app: &AppHandle,
+    path: &str,
+    num_bars: usize,
+) -> Result<Waveform, String> {
+    match get_audio_waveform(path, num_bars) {
+        Ok(waveform) if waveform.peaks.iter().any(|p| *p > 0.0) => return Ok(waveform),
+        _ => {}
+    }
+
+    let (metadata, _) = probe_video(app, Path::new(path))?;
+    if metadata.audio_tracks == 0 {
+        return Ok(Waveform::flat(num_bars));
+    }
+    get_waveform_ffmpeg(app, path, num_bars)
+}
+
+// Simpan peak ke waveform_data (yang digambar UI) dan RMS per bar ke waveform_rms
+fn store_waveform(db_arc: &Arc<Mutex<Connection>>, asset_id: i64, waveform: &Waveform) {
+    let peaks_json = serde_json::to_string(&waveform.peaks).unwrap_or("[]".to_string());
+    let rms_json = serde_json::to_string(&waveform.rms).unwrap_or("[]".to_string());
+
+    if let Ok(conn) = db_arc.lock() {
+        let _ = conn.execute(
+            "UPDATE assets SET waveform_data = ?1, waveform_rms = ?2 WHERE id = ?3",
+            rusqlite::params![peaks_json, rms_json, asset_id],
+        );
+    }
+}
+
+// Hitung waveform + baca metadata embedded + fitur audio satu file lalu simpan ke DB
+pub fn update_waveform_and_metadata(
+    db_arc: &Arc<Mutex<Connection>>,
+    asset_id: i64,
+    path: &str,
+    filename: &str,
+    file_size: i64,
+) {
+    // B. Proses Berat (Decode Audio) - Tidak mengunci DB
+    // Ingat: function get_audio_waveform kita return peak & RMS per bar (0 s/d 1)
+    let waveform_result = get_audio_waveform(path, 100);
+
+    match waveform_result {
+        Ok(waveform) => {
+            // C. Update DB (Hanya lock sebentar saat update row ini saja)
+            store_waveform(db_arc, asset_id, &waveform);
+        }
+        Err(e) => {
+            println!("Gagal process {}: {}", filename, e);
+            // Lanjut ke file berikutnya meski error
+        }
+    }
+
+    // Sekalian baca metadata embedded (bext, iXML, ID3, Vorbis comment)
+    match read_audio_metadata(path, file_size) {
+        Ok((meta, duration_sec)) => {
+            let search_text = audio_search_text(&meta);
+            let metadata_json = serde_json::to_string(&AssetMetadata::Audio(Box::new(meta)))
+                .unwrap_or("{}".to_string());
+
+            // Durasi dari header adalah sumber utama; 0 = header tidak punya n_frames,
+            // jangan timpa durasi yang sudah ada
+            if let Ok(conn) = db_arc.lock() {
+                let _ = conn.execute(
+                    "UPDATE assets SET metadata = ?1, search_text = ?2,
+                            duration_sec = CASE WHEN ?3 > 0 THEN ?3 ELSE duration_sec END
+                     WHERE id = ?4",
+                    rusqlite::params![metadata_json, search_text, duration_sec, asset_id],
+                );
+            }
+        }
+        Err(e) => {
+            println!("Gagal baca metadata {}: {}", filename, e);
+        }
+    }
+
+    // Fitur spektral untuk pencarian "mirip", butuh durasi header yang baru disimpan
+    update_audio_features(db_arc, asset_id, path, filename);
 }
 
 #[tauri::command]
@@ -112,13 +438,13 @@ pub fn generate_missing_waveforms(
     state.cancel_scan.store(false, Ordering::SeqCst);
     let cancel_flag = state.cancel_scan.clone();
 
-    let to_process: Vec<(i64, String, String)> = {
+    let to_process: Vec<(i64, String, String, i64, String)> = {
         let conn = db_arc.lock().map_err(|e| e.to_string())?;
         let mut stmt = conn
             .prepare(
-                // Cari file audio yang waveform-nya masih default '[]' atau NULL
-                "SELECT id, original_path, filename FROM assets 
-             WHERE type = 'audio' AND (waveform_data = '[]' OR waveform_data IS NULL)",
+                // Cari file audio/video yang waveform-nya masih default '[]' atau NULL
+                "SELECT id, original_path, filename, file_size, type FROM assets 
+             WHERE type IN ('audio', 'video') AND (waveform_data = '[]' OR waveform_data IS NULL)",
             )
             .map_err(|e| e.to_string())?;
 
@@ -128,6 +454,8 @@ pub fn generate_missing_waveforms(
                     row.get::<_, i64>(0)?,    // id
                     row.get::<_, String>(1)?, // path
                     row.get::<_, String>(2)?, // filename
+                    row.get::<_, i64>(3)?,    // file_size
+                    row.get::<_, String>(4)?, // type
                 ))
             })
             .map_err(|e| e.to_string())?;
@@ -147,19 +475,171 @@ pub fn generate_missing_waveforms(
     std::thread::spawn(move || {
         println!("Background process started for {} files", total_files);
 
+        to_process
+            .par_iter()
+            .for_each(|(id, path, filename, file_size, asset_type)| {
+                // Check cancel flag FIRST before processing
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return;
+                }
+
+                let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+                // A. Emit Event: "Sedang memproses lagu X..."
+                let _ = app.emit(
+                    "waveform-progress",
+                    ProgressEvent {
+                        name: "Sound".to_string(),
+                        current,
+                        total: total_files,
+                        filename: filename.clone(),
+                        status: "processing".to_string(),
+                    },
+                );
+
+                if asset_type == "video" {
+                    // Metadata video diisi ffprobe, di sini cukup waveform-nya
+                    match get_video_waveform(&app, path, 100) {
+                        Ok(waveform) => store_waveform(&db_arc, *id, &waveform),
+                        Err(e) => println!("Gagal process {}: {}", filename, e),
+                    }
+                    return;
+                }
+
+                update_waveform_and_metadata(&db_arc, *id, path, filename, *file_size);
+            });
+
+        // D. Emit Event Selesai
+        let _ = app.emit(
+            "waveform-progress",
+            ProgressEvent {
+                name: "Sound".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    // Command utama langsung return, tidak menunggu thread selesai
+    Ok(format!(
+        "Memulai proses background untuk {} file...",
+        total_files
+    ))
+}
+
+// Pecah file multi-hit jadi beberapa region berdasarkan jeda hening.
+// Envelope dihitung per window 10 ms, jeda lebih pendek dari min_gap_sec digabung,
+// region yang lebih pendek dari min_region_sec dibuang.
+pub fn detect_regions(
+    audio: &DecodedAudio,
+    threshold_db: f32,
+    min_gap_sec: f64,
+    min_region_sec: f64,
+) -> Vec<(f64, f64)> {
+    if audio.sample_rate == 0 {
+        return Vec::new();
+    }
+
+    let rate = audio.sample_rate as f64;
+    let threshold = 10f32.powf(threshold_db / 20.0);
+    let window = (audio.sample_rate as usize / 100).max(1);
+
+    // 1. Kumpulkan window yang berbunyi (dalam satuan frame)
+    let mut loud: Vec<(usize, usize)> = Vec::new();
+    for (i, chunk) in audio.samples.chunks(window * audio.channels).enumerate() {
+        let peak = chunk.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        if peak <= threshold {
+            continue;
+        }
+
+        let start = i * window;
+        let end = start + chunk.len() / audio.channels;
+        match loud.last_mut() {
+            Some(last) if last.1 == start => last.1 = end,
+            _ => loud.push((start, end)),
+        }
+    }
+
+    // 2. Gabungkan region yang jedanya terlalu pendek (ekor/reverb satu hit)
+    let min_gap = (min_gap_sec * rate) as usize;
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in loud {
+        match merged.last_mut() {
+            Some(last) if start - last.1 < min_gap => last.1 = end,
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+        .into_iter()
+        .filter(|(start, end)| (end - start) as f64 / rate >= min_region_sec)
+        .map(|(start, end)| (start as f64 / rate, end as f64 / rate))
+        .collect()
+}
+
+pub const DEFAULT_SILENCE_THRESHOLD_DB: f32 = -60.0;
+pub const DEFAULT_REGION_THRESHOLD_DB: f32 = -50.0;
+pub const DEFAULT_REGION_MIN_GAP_MS: u32 = 100;
+const MIN_REGION_SEC: f64 = 0.02;
+
+#[tauri::command]
+pub fn generate_trim_points(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    threshold_db: Option<f32>,
+    overwrite: bool,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let threshold_db = threshold_db.unwrap_or(DEFAULT_SILENCE_THRESHOLD_DB);
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        // overwrite = true dipakai saat user mengganti threshold
+        let sql = if overwrite {
+            "SELECT id, original_path, filename FROM assets WHERE type = 'audio'"
+        } else {
+            "SELECT id, original_path, filename FROM assets
+             WHERE type = 'audio' AND trim_start_sec IS NULL"
+        };
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,    // id
+                    row.get::<_, String>(1)?, // path
+                    row.get::<_, String>(2)?, // filename
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Semua trim point sudah lengkap.".to_string());
+    }
+
+    let processed_count = std::sync::Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
         to_process.par_iter().for_each(|(id, path, filename)| {
-            // Check cancel flag FIRST before processing
             if cancel_flag.load(Ordering::SeqCst) {
                 return;
             }
 
             let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
 
-            // A. Emit Event: "Sedang memproses lagu X..."
             let _ = app.emit(
-                "waveform-progress",
+                "trim-progress",
                 ProgressEvent {
-                    name: "Sound".to_string(),
+                    name: "Trim".to_string(),
                     current,
                     total: total_files,
                     filename: filename.clone(),
@@ -167,34 +647,33 @@ pub fn generate_missing_waveforms(
                 },
             );
 
-            // B. Proses Berat (Decode Audio) - Tidak mengunci DB
-            // Ingat: function get_audio_waveform kita sudah return Vec<f32> (-1 s/d 1)
-            let waveform_result = get_audio_waveform(path, 100);
-
-            match waveform_result {
-                Ok(data) => {
-                    let json_data = serde_json::to_string(&data).unwrap_or("[]".to_string());
+            match decode_audio(path) {
+                Ok(audio) => {
+                    // File yang seluruhnya hening disimpan sebagai 0..0
+                    let (start, end) =
+                        detect_silence_bounds(&audio, threshold_db).unwrap_or((0.0, 0.0));
 
-                    // C. Update DB (Hanya lock sebentar saat update row ini saja)
+                    // Panjang hasil decode hanya mengisi durasi yang belum diketahui dari header
                     if let Ok(conn) = db_arc.lock() {
                         let _ = conn.execute(
-                            "UPDATE assets SET waveform_data = ?1 WHERE id = ?2",
-                            rusqlite::params![json_data, id],
+                            "UPDATE assets SET trim_start_sec = ?1, trim_end_sec = ?2,
+                                    duration_sec = CASE WHEN COALESCE(duration_sec, 0) > 0
+                                                        THEN duration_sec ELSE ?3 END
+                             WHERE id = ?4",
+                            rusqlite::params![start, end, audio.duration_sec(), id],
                         );
                     }
                 }
                 Err(e) => {
-                    println!("Gagal process {}: {}", filename, e);
-                    // Lanjut ke file berikutnya meski error
+                    println!("Gagal deteksi silence {}: {}", filename, e);
                 }
             }
         });
 
-        // D. Emit Event Selesai
         let _ = app.emit(
-            "waveform-progress",
+            "trim-progress",
             ProgressEvent {
-                name: "Sound".to_string(),
+                name: "Trim".to_string(),
                 current: total_files,
                 total: total_files,
                 filename: "Selesai!".to_string(),
@@ -203,9 +682,500 @@ pub fn generate_missing_waveforms(
         );
     });
 
-    // Command utama langsung return, tidak menunggu thread selesai
     Ok(format!(
-        "Memulai proses background untuk {} file...",
+        "Memulai deteksi silence untuk {} file...",
+        total_files
+    ))
+}
+
+pub fn write_wav_range(
+    audio: &DecodedAudio,
+    start_frame: usize,
+    end_frame: usize,
+    out_path: &Path,
+) -> Result<(), String> {
+    let (bits_per_sample, sample_format) = if audio.is_float {
+        (32, hound::SampleFormat::Float)
+    } else {
+        (
+            audio.bits_per_sample.unwrap_or(16).clamp(8, 32) as u16,
+            hound::SampleFormat::Int,
+        )
+    };
+
+    let spec = hound::WavSpec {
+        channels: audio.channels as u16,
+        sample_rate: audio.sample_rate,
+        bits_per_sample,
+        sample_format,
+    };
+
+    let mut writer = hound::WavWriter::create(out_path, spec).map_err(|e| e.to_string())?;
+    let range = &audio.samples[start_frame * audio.channels..end_frame * audio.channels];
+
+    match sample_format {
+        hound::SampleFormat::Float => {
+            for &s in range {
+                writer.write_sample(s).map_err(|e| e.to_string())?;
+            }
+        }
+        hound::SampleFormat::Int => {
+            // Skala balik ke integer sesuai bit depth asli (lossless untuk PCM <= 24 bit)
+            let max = (1i64 << (bits_per_sample - 1)) as f64;
+            for &s in range {
+                let value = (s as f64 * max).round().clamp(-max, max - 1.0) as i32;
+                writer.write_sample(value).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+
+    writer.finalize().map_err(|e| e.to_string())
+}
+
+// Export beberapa potongan (start, end, output) dari satu file audio sumber
+fn export_audio_ranges(
+    app: &AppHandle,
+    path: &str,
+    ext: &str,
+    ranges: &[(f64, f64, PathBuf)],
+) -> Result<(), String> {
+    if ext == "wav" {
+        // Decode sekali saja lalu tulis tiap range
+        let audio = decode_audio(path).map_err(|e| e.to_string())?;
+        let rate = audio.sample_rate as f64;
+
+        for (start, end, out_path) in ranges {
+            let end_frame = ((end * rate).round() as usize).min(audio.frames());
+            let start_frame = ((start * rate).round() as usize).min(end_frame);
+            write_wav_range(&audio, start_frame, end_frame, out_path)?;
+        }
+        return Ok(());
+    }
+
+    // Selain WAV pakai ffmpeg. FLAC tetap lossless karena di-encode ulang ke FLAC.
+    for (start, end, out_path) in ranges {
+        let mut args = vec![
+            "-i".to_string(),
+            path.to_string(),
+            "-ss".to_string(),
+            format!("{:.6}", start),
+            "-to".to_string(),
+            format!("{:.6}", end),
+            "-map_metadata".to_string(),
+            "0".to_string(),
+        ];
+        if ext == "flac" {
+            args.extend(["-c:a".to_string(), "flac".to_string()]);
+        }
+        args.push(out_path.to_string_lossy().to_string());
+
+        run_ffmpeg(app, &args)?;
+    }
+
+    Ok(())
+}
+
+pub fn build_export_path(
+    original_path: &str,
+    output_dir: Option<String>,
+    suffix: &str,
+    ext: &str,
+) -> Result<PathBuf, String> {
+    let original = Path::new(original_path);
+    let stem = original
+        .file_stem()
+        .ok_or("Nama file tidak valid")?
+        .to_string_lossy();
+
+    let dir = match output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => original
+            .parent()
+            .ok_or("Gagal mendapatkan folder induk")?
+            .to_path_buf(),
+    };
+
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    // ffmpeg jalan dengan -y dan WavWriter::create memotong file, jadi file yang sudah
+    // ada (export sebelumnya atau milik user) dihindari dengan nomor tambahan
+    let mut out_path = dir.join(format!("{}_{}.{}", stem, suffix, ext));
+    let mut n = 2;
+    while out_path.exists() {
+        out_path = dir.join(format!("{}_{}_{}.{}", stem, suffix, n, ext));
+        n += 1;
+    }
+
+    Ok(out_path)
+}
+
+#[tauri::command]
+pub async fn export_trimmed_audio(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+    output_dir: Option<String>,
+) -> Result<String, String> {
+    let (path, extension, trim_start, trim_end): (String, String, Option<f64>, Option<f64>) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT original_path, extension, trim_start_sec, trim_end_sec FROM assets
+             WHERE id = ?1 AND type = 'audio'",
+            [asset_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Asset tidak ditemukan: {}", e))?
+    };
+
+    let (start, end) = match (trim_start, trim_end) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Err("Trim point belum dideteksi untuk asset ini".to_string()),
+    };
+
+    if end <= start {
+        return Err("File seluruhnya hening, tidak ada yang bisa di-export".to_string());
+    }
+
+    let ext = extension.to_lowercase();
+    let out_path = build_export_path(&path, output_dir, "trimmed", &ext)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        export_audio_ranges(&app, &path, &ext, &[(start, end, out_path.clone())])?;
+        Ok(out_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn generate_regions(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Vec<i64>,
+    threshold_db: Option<f32>,
+    min_gap_ms: Option<u32>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let threshold_db = threshold_db.unwrap_or(DEFAULT_REGION_THRESHOLD_DB);
+    let min_gap_sec = min_gap_ms.unwrap_or(DEFAULT_REGION_MIN_GAP_MS) as f64 / 1000.0;
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    // asset_ids kosong = proses semua asset audio
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT id, original_path, filename FROM assets WHERE type = 'audio'")
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,    // id
+                    row.get::<_, String>(1)?, // path
+                    row.get::<_, String>(2)?, // filename
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.filter_map(|r| r.ok())
+            .filter(|(id, _, _)| asset_ids.is_empty() || asset_ids.contains(id))
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Tidak ada file audio untuk diproses.".to_string());
+    }
+
+    let processed_count = std::sync::Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, path, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "region-progress",
+                ProgressEvent {
+                    name: "Region".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let audio = match decode_audio(path) {
+                Ok(audio) => audio,
+                Err(e) => {
+                    println!("Gagal deteksi region {}: {}", filename, e);
+                    return;
+                }
+            };
+
+            let regions = detect_regions(&audio, threshold_db, min_gap_sec, MIN_REGION_SEC);
+
+            // Ganti region lama milik asset ini dengan hasil baru
+            if let Ok(mut conn) = db_arc.lock() {
+                if let Err(e) = replace_regions(&mut conn, *id, &regions) {
+                    println!("Gagal simpan region {}: {}", filename, e);
+                }
+            }
+        });
+
+        let _ = app.emit(
+            "region-progress",
+            ProgressEvent {
+                name: "Region".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai deteksi region untuk {} file...",
         total_files
     ))
 }
+
+pub fn map_region_row(row: &rusqlite::Row) -> rusqlite::Result<Region> {
+    Ok(Region {
+        id: row.get("id")?,
+        asset_id: row.get("asset_id")?,
+        region_index: row.get("region_index")?,
+        start_sec: row.get("start_sec")?,
+        end_sec: row.get("end_sec")?,
+        filename: row.get("filename")?,
+        extension: row.get("extension")?,
+        original_path: row.get("original_path")?,
+    })
+}
+
+// Ganti semua region milik asset dengan daftar (start, end) baru
+pub fn replace_regions(
+    conn: &mut Connection,
+    asset_id: i64,
+    regions: &[(f64, f64)],
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM regions WHERE asset_id = ?1", [asset_id])?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO regions (asset_id, region_index, start_sec, end_sec)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (index, (start, end)) in regions.iter().enumerate() {
+            stmt.execute(rusqlite::params![asset_id, index as i64, start, end])?;
+        }
+    }
+    tx.commit()
+}
+
+// Hanya region audio; baris lama hasil deteksi scene video (sebelum ada tabel shots)
+// tidak boleh ikut tampil atau di-export sebagai audio
+const REGION_SELECT: &str = "SELECT r.id, r.asset_id, r.region_index, r.start_sec, r.end_sec,
+            a.filename, a.extension, a.original_path
+     FROM regions r JOIN assets a ON a.id = r.asset_id
+     WHERE a.type = 'audio'";
+
+#[tauri::command]
+pub fn get_asset_regions(state: State<'_, DbState>, asset_id: i64) -> Result<Vec<Region>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} AND r.asset_id = ?1 ORDER BY r.region_index ASC",
+            REGION_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([asset_id], map_region_row)
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_regions_paginated(
+    state: State<'_, DbState>,
+    page: u32,
+    page_size: u32,
+    query: String,
+) -> Result<PaginatedResponse<Region>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut sql_where = String::new();
+    let mut params_values: Vec<String> = Vec::new();
+
+    // Pencarian sama seperti asset: semua token harus cocok ke filename/path induk
+    for token in query.split_whitespace() {
+        sql_where.push_str(" AND (a.filename LIKE ? OR a.original_path LIKE ?)");
+        let wildcard = format!("%{}%", token);
+        params_values.push(wildcard.clone());
+        params_values.push(wildcard);
+    }
+
+    let params_refs: Vec<&dyn ToSql> = params_values.iter().map(|p| p as &dyn ToSql).collect();
+
+    let total_items: u64 = conn
+        .query_row(
+            &format!(
+                "SELECT COUNT(*) FROM regions r JOIN assets a ON a.id = r.asset_id
+                 WHERE a.type = 'audio'{}",
+                sql_where
+            ),
+            params_refs.as_slice(),
+            |row| row.get::<_, i64>(0).map(|x| x as u64),
+        )
+        .map_err(|e| format!("Gagal hitung total: {}", e))?;
+
+    let total_pages = (total_items as f64 / page_size as f64).ceil() as u64;
+    let offset = (page.max(1) - 1) * page_size;
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "{}{} ORDER BY r.asset_id ASC, r.region_index ASC LIMIT {} OFFSET {}",
+            REGION_SELECT, sql_where, page_size, offset
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let data = stmt
+        .query_map(params_refs.as_slice(), map_region_row)
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(PaginatedResponse {
+        data,
+        total_items,
+        total_pages,
+        current_page: page,
+    })
+}
+
+#[tauri::command]
+pub async fn export_regions(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+    output_dir: Option<String>,
+) -> Result<Vec<String>, String> {
+    let regions = get_asset_regions(state, asset_id)?;
+
+    let first = regions
+        .first()
+        .ok_or("Asset ini belum punya region, jalankan deteksi region dulu")?;
+    let path = first.original_path.clone();
+    let ext = first.extension.to_lowercase();
+
+    let mut ranges = Vec::with_capacity(regions.len());
+    for region in &regions {
+        let suffix = format!("{:02}", region.region_index + 1);
+        let out_path = build_export_path(&path, output_dir.clone(), &suffix, &ext)?;
+        ranges.push((region.start_sec, region.end_sec, out_path));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        export_audio_ranges(&app, &path, &ext, &ranges)?;
+        Ok(ranges
+            .iter()
+            .map(|(_, _, out_path)| out_path.to_string_lossy().to_string())
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mono(samples: Vec<f32>, sample_rate: u32) -> DecodedAudio {
+        DecodedAudio {
+            samples,
+            sample_rate,
+            channels: 1,
+            bits_per_sample: Some(16),
+            is_float: false,
+            total_frames: None,
+        }
+    }
+
+    // Burst 0.5 di [start, end) detik, sisanya hening
+    fn bursts(sample_rate: u32, total_sec: f64, ranges: &[(f64, f64)]) -> DecodedAudio {
+        let rate = sample_rate as f64;
+        let samples = (0..(total_sec * rate) as usize)
+            .map(|i| {
+                let t = i as f64 / rate;
+                if ranges.iter().any(|(s, e)| t >= *s && t < *e) {
+                    0.5
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        mono(samples, sample_rate)
+    }
+
+    #[test]
+    fn silence_bounds_all_silent() {
+        let audio = mono(vec![0.0; 1000], 1000);
+        assert_eq!(detect_silence_bounds(&audio, -60.0), None);
+    }
+
+    #[test]
+    fn silence_bounds_trims_leading_and_trailing_silence() {
+        let audio = bursts(1000, 1.0, &[(0.25, 0.75)]);
+        assert_eq!(detect_silence_bounds(&audio, -60.0), Some((0.25, 0.75)));
+    }
+
+    #[test]
+    fn regions_all_silent() {
+        let audio = mono(vec![0.0; 1000], 1000);
+        assert!(detect_regions(&audio, -50.0, 0.1, 0.02).is_empty());
+    }
+
+    #[test]
+    fn regions_merge_gaps_shorter_than_min_gap() {
+        // Jeda 50 ms digabung, jeda 300 ms memisahkan region
+        let audio = bursts(1000, 2.0, &[(0.1, 0.3), (0.35, 0.5), (0.8, 1.0)]);
+
+        let regions = detect_regions(&audio, -50.0, 0.1, 0.02);
+        assert_eq!(regions, vec![(0.1, 0.5), (0.8, 1.0)]);
+
+        let regions = detect_regions(&audio, -50.0, 0.01, 0.02);
+        assert_eq!(regions.len(), 3);
+    }
+
+    #[test]
+    fn regions_drop_hits_shorter_than_min_region() {
+        let audio = bursts(1000, 1.0, &[(0.1, 0.11), (0.5, 0.7)]);
+        assert_eq!(detect_regions(&audio, -50.0, 0.1, 0.02), vec![(0.5, 0.7)]);
+    }
+
+    #[test]
+    fn wav_range_clamps_int_rescale() {
+        let audio = mono(vec![1.0, -1.0, 0.5, 2.0, -2.0], 8000);
+        let path = std::env::temp_dir().join(format!("wav_range_{}.wav", std::process::id()));
+
+        write_wav_range(&audio, 0, 5, &path).unwrap();
+        let written: Vec<i32> = hound::WavReader::open(&path)
+            .unwrap()
+            .samples::<i32>()
+            .map(|s| s.unwrap())
+            .collect();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(written, vec![32767, -32768, 16384, 32767, -32768]);
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+
+use image::RgbaImage;
+use resvg::{tiny_skia, usvg};
+
+// SVG raksasa (peta, hasil export CAD) tidak dirender supaya thumbnail tidak macet
+const MAX_SVG_BYTES: u64 = 20 * 1024 * 1024;
+
+pub fn is_svg(ext: &str) -> bool {
+    matches!(ext.to_lowercase().as_str(), "svg" | "svgz")
+}
+
+// Font sistem cukup di-load sekali untuk semua SVG
+fn fontdb() -> Arc<usvg::fontdb::Database> {
+    static FONTDB: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTDB
+        .get_or_init(|| {
+            let mut db = usvg::fontdb::Database::new();
+            db.load_system_fonts();
+            Arc::new(db)
+        })
+        .clone()
+}
+
+// Parse SVG/SVGZ. usvg tidak menjalankan <script> dan hanya membaca
+// gambar eksternal relatif terhadap folder file.
+fn parse_svg(path: &str) -> Result<usvg::Tree, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_SVG_BYTES {
+        return Err(format!("SVG terlalu besar ({} bytes)", size));
+    }
+
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let options = usvg::Options {
+        resources_dir: Path::new(path).parent().map(|p| p.to_path_buf()),
+        fontdb: fontdb(),
+        ..Default::default()
+    };
+
+    usvg::Tree::from_data(&data, &options).map_err(|e| e.to_string())
+}
+
+// Ukuran intrinsik SVG: atribut width/height, fallback ke ukuran viewBox
+pub fn svg_dimensions(path: &str) -> Result<(u32, u32), String> {
+    let size = parse_svg(path)?.size();
+    Ok((
+        size.width().ceil().max(1.0) as u32,
+        size.height().ceil().max(1.0) as u32,
+    ))
+}
+
+// Render SVG dengan sisi terpanjang = max_side (SVG kecil ikut diperbesar supaya tetap tajam)
+pub fn rasterize_svg(path: &str, max_side: u32) -> Result<RgbaImage, String> {
+    let tree = parse_svg(path)?;
+    let size = tree.size();
+    let scale = max_side as f32 / size.width().max(size.height());
+
+    let width = (size.width() * scale).ceil().max(1.0) as u32;
+    let height = (size.height() * scale).ceil().max(1.0) as u32;
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or("Ukuran SVG tidak valid")?;
+
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia menyimpan warna premultiplied, thumbnail butuh RGBA biasa
+    let rgba = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|p| {
+            let c = p.demultiply();
+            [c.red(), c.green(), c.blue(), c.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(width, height, rgba).ok_or("Buffer SVG tidak valid".to_string())
+}
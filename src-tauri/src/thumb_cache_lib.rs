@@ -0,0 +1,407 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+use crate::image_lib::{
+    default_thumbnails_dir, load_thumbnail_settings, save_thumbnail_settings, thumbnails_dir,
+};
+use crate::models::{DbState, ThumbnailCacheReport};
+
+// Key dihitung dari ukuran + mtime + 64 KB awal + 64 KB akhir file: cukup cepat untuk
+// video besar. Sampel saja tidak menangkap edit di tengah file dengan ukuran sama,
+// jadi mtime ikut di-hash dan watcher me-reset key saat file berubah (rekey_if_changed)
+const KEY_SAMPLE_BYTES: u64 = 64 * 1024;
+const KEY_HEX_LEN: usize = 32;
+// File yang baru ditulis bisa belum tercatat di DB (generate thumbnail masih jalan)
+const GC_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+// Key cache thumbnail berdasarkan isi file, bukan id asset: id bisa dipakai ulang
+// setelah clear_db, sedangkan file yang sama selalu dapat key yang sama
+pub fn content_key(path: &str) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let meta = file.metadata().map_err(|e| e.to_string())?;
+    let size = meta.len();
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+    hasher.update(mtime.as_secs().to_le_bytes());
+    hasher.update(mtime.subsec_nanos().to_le_bytes());
+
+    let mut buf = Vec::new();
+    (&mut file)
+        .take(KEY_SAMPLE_BYTES)
+        .read_to_end(&mut buf)
+        .map_err(|e| e.to_string())?;
+    hasher.update(&buf);
+
+    // File <= 128 KB tetap ter-hash seluruhnya
+    if size > KEY_SAMPLE_BYTES {
+        file.seek(SeekFrom::Start(
+            KEY_SAMPLE_BYTES.max(size - KEY_SAMPLE_BYTES),
+        ))
+        .map_err(|e| e.to_string())?;
+        buf.clear();
+        file.take(KEY_SAMPLE_BYTES)
+            .read_to_end(&mut buf)
+            .map_err(|e| e.to_string())?;
+        hasher.update(&buf);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .take(KEY_HEX_LEN / 2)
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+pub fn stored_cache_key(conn: &Connection, asset_id: i64) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT thumbnail_key FROM assets WHERE id = ?1",
+        [asset_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+    .map(Option::flatten)
+}
+
+// Key yang sudah tersimpan, atau hitung dari file lalu simpan (mis. scrub strip
+// dibuat sebelum thumbnail)
+pub fn asset_cache_key(conn: &Connection, asset_id: i64, path: &str) -> Result<String, String> {
+    if let Some(key) = stored_cache_key(conn, asset_id)? {
+        return Ok(key);
+    }
+
+    let key = content_key(path)?;
+    conn.execute(
+        "UPDATE assets SET thumbnail_key = ?1 WHERE id = ?2",
+        rusqlite::params![key, asset_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+// Dipanggil watcher saat file yang sudah terdaftar berubah: kalau key lama tidak cocok
+// lagi dengan isi file, thumbnail & key di-reset supaya generate_missing_thumbnails
+// membuat ulang dengan key baru. Entry cache lama dibuang oleh collect_garbage.
+pub fn rekey_if_changed(conn: &Connection, path: &str) -> Result<(), String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT thumbnail_key FROM assets WHERE original_path = ?1",
+            [path],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+
+    let Some(stored) = stored else {
+        return Ok(());
+    };
+    if content_key(path).ok().as_deref() == Some(stored.as_str()) {
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE assets SET thumbnail_key = NULL, thumbnail_path = NULL, blurhash = NULL
+         WHERE original_path = ?1",
+        [path],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Nama file cache: {key}_{size}.webp, {key}_anim.webp, {key}_strip.webp/.json.
+// Format lama berbasis id ({id}.webp, {id}_256.webp, ...) juga dikenali supaya ikut
+// dibersihkan. File lain di folder cache tidak pernah disentuh.
+fn cache_entry_key(name: &str) -> Option<&str> {
+    let stem = name
+        .strip_suffix(".webp")
+        .or_else(|| name.strip_suffix(".json"))?;
+    let (key, suffix) = stem.split_once('_').unwrap_or((stem, ""));
+
+    let suffix_ok =
+        suffix.is_empty() || suffix == "anim" || suffix == "strip" || suffix.parse::<u32>().is_ok();
+    let hashed =
+        key.len() == KEY_HEX_LEN && key.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    let legacy = !key.is_empty() && key.bytes().all(|b| b.is_ascii_digit());
+
+    (suffix_ok && (hashed || legacy)).then_some(key)
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    key: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn scan_cache(dir: &Path) -> Vec<CacheEntry> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let key = cache_entry_key(&name)?.to_string();
+            let meta = entry.metadata().ok().filter(|m| m.is_file())?;
+            Some(CacheEntry {
+                path: entry.path(),
+                key,
+                size: meta.len(),
+                modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect()
+}
+
+// Key + path thumbnail yang masih dipakai asset
+fn referenced_entries(conn: &Connection) -> Result<(HashSet<String>, HashSet<PathBuf>), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT thumbnail_key, thumbnail_path FROM assets
+             WHERE thumbnail_key IS NOT NULL OR thumbnail_path IS NOT NULL",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows: Vec<(Option<String>, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let keys = rows.iter().filter_map(|(key, _)| key.clone()).collect();
+    let paths = rows
+        .into_iter()
+        .filter_map(|(_, path)| path.map(PathBuf::from))
+        .collect();
+    Ok((keys, paths))
+}
+
+// Hapus file yang tidak dipakai asset mana pun, lalu (kalau ada batas) buang grup
+// file paling lama sampai ukuran cache di bawah max_cache_mb
+pub fn collect_garbage(
+    dir: &Path,
+    conn: &Arc<Mutex<Connection>>,
+    max_cache_mb: Option<u64>,
+) -> Result<ThumbnailCacheReport, String> {
+    let (keys, paths) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        referenced_entries(&conn)?
+    };
+
+    let mut report = ThumbnailCacheReport {
+        cache_dir: dir.to_string_lossy().to_string(),
+        removed_files: 0,
+        reclaimed_bytes: 0,
+        total_bytes: 0,
+    };
+
+    let now = SystemTime::now();
+    let mut kept = Vec::new();
+    for entry in scan_cache(dir) {
+        let recent = now
+            .duration_since(entry.modified)
+            .map(|age| age < GC_GRACE_PERIOD)
+            .unwrap_or(true);
+
+        if recent || keys.contains(&entry.key) || paths.contains(&entry.path) {
+            report.total_bytes += entry.size;
+            kept.push(entry);
+        } else if std::fs::remove_file(&entry.path).is_ok() {
+            report.removed_files += 1;
+            report.reclaimed_bytes += entry.size;
+        }
+    }
+
+    if let Some(max_mb) = max_cache_mb {
+        evict_to_limit(kept, max_mb * 1024 * 1024, &mut report);
+    }
+    Ok(report)
+}
+
+// Semua file satu key (ukuran thumbnail, preview animasi, scrub strip) dibuang bersama,
+// urut dari yang paling lama dibuat. thumbnail_path asset-nya tidak diubah: file yang
+// hilang dibuat ulang saat diminta (image_lib::get_thumbnail_path), bukan oleh
+// generate_missing_thumbnails yang akan langsung membuatnya lagi lalu di-evict lagi.
+fn evict_to_limit(entries: Vec<CacheEntry>, max_bytes: u64, report: &mut ThumbnailCacheReport) {
+    if report.total_bytes <= max_bytes {
+        return;
+    }
+
+    let mut groups: HashMap<String, (SystemTime, Vec<CacheEntry>)> = HashMap::new();
+    for entry in entries {
+        let group = groups
+            .entry(entry.key.clone())
+            .or_insert((SystemTime::UNIX_EPOCH, Vec::new()));
+        group.0 = group.0.max(entry.modified);
+        group.1.push(entry);
+    }
+
+    let mut groups: Vec<(SystemTime, Vec<CacheEntry>)> = groups.into_values().collect();
+    groups.sort_by_key(|(modified, _)| *modified);
+
+    for (_, files) in groups {
+        if report.total_bytes <= max_bytes {
+            break;
+        }
+
+        for file in files {
+            if std::fs::remove_file(&file.path).is_err() {
+                continue;
+            }
+            report.removed_files += 1;
+            report.reclaimed_bytes += file.size;
+            report.total_bytes -= file.size;
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn gc_thumbnail_cache(
+    app: AppHandle,
+    state: State<'_, DbState>,
+) -> Result<ThumbnailCacheReport, String> {
+    let db_arc = state.conn.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let max_cache_mb = load_thumbnail_settings(&app).max_cache_mb;
+        collect_garbage(&thumbnails_dir(&app)?, &db_arc, max_cache_mb)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// None = tanpa batas. Batas baru langsung diterapkan.
+#[tauri::command]
+pub async fn set_thumbnail_cache_limit(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    max_cache_mb: Option<u64>,
+) -> Result<ThumbnailCacheReport, String> {
+    if max_cache_mb == Some(0) {
+        return Err("Batas cache minimal 1 MB".to_string());
+    }
+    let db_arc = state.conn.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut settings = load_thumbnail_settings(&app);
+        settings.max_cache_mb = max_cache_mb;
+        save_thumbnail_settings(&app, &settings)?;
+
+        collect_garbage(&thumbnails_dir(&app)?, &db_arc, max_cache_mb)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Pindahkan cache thumbnail ke folder lain (mis. drive lain); None = kembali ke app data.
+// Semua file disalin dulu, jadi kalau gagal di tengah jalan cache lama masih utuh.
+#[tauri::command]
+pub async fn move_thumbnail_cache(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    new_dir: Option<String>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let old_dir = thumbnails_dir(&app)?;
+        let target_dir = match &new_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => default_thumbnails_dir(&app)?,
+        };
+        std::fs::create_dir_all(&target_dir).map_err(|e| e.to_string())?;
+
+        if old_dir.canonicalize().ok() == target_dir.canonicalize().ok() {
+            return Ok(format!(
+                "Cache thumbnail sudah di {}",
+                target_dir.to_string_lossy()
+            ));
+        }
+
+        // 1. Salin semua file cache (mtime dipertahankan untuk urutan eviction)
+        let entries = scan_cache(&old_dir);
+        let mut copied: Vec<PathBuf> = Vec::new();
+        for entry in &entries {
+            let target = target_dir.join(entry.path.file_name().unwrap_or_default());
+            if let Err(e) = std::fs::copy(&entry.path, &target) {
+                for path in &copied {
+                    let _ = std::fs::remove_file(path);
+                }
+                return Err(format!(
+                    "Gagal menyalin {}: {}",
+                    entry.path.to_string_lossy(),
+                    e
+                ));
+            }
+            let _ = File::options()
+                .write(true)
+                .open(&target)
+                .and_then(|file| file.set_modified(entry.modified));
+            copied.push(target);
+        }
+
+        // 2. Arahkan thumbnail_path ke folder baru
+        {
+            let mut conn = db_arc.lock().map_err(|e| e.to_string())?;
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            let rows: Vec<(i64, String)> = {
+                let mut stmt = tx
+                    .prepare(
+                        "SELECT id, thumbnail_path FROM assets WHERE thumbnail_path IS NOT NULL",
+                    )
+                    .map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                    .map_err(|e| e.to_string())?
+                    .filter_map(|r| r.ok())
+                    .collect();
+                rows
+            };
+
+            for (id, path) in rows {
+                if let Ok(rest) = Path::new(&path).strip_prefix(&old_dir) {
+                    tx.execute(
+                        "UPDATE assets SET thumbnail_path = ?1 WHERE id = ?2",
+                        rusqlite::params![target_dir.join(rest).to_string_lossy().to_string(), id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+            tx.commit().map_err(|e| e.to_string())?;
+        }
+
+        // 3. Simpan lokasi baru, baru hapus file lama
+        let mut settings = load_thumbnail_settings(&app);
+        settings.cache_dir = new_dir;
+        save_thumbnail_settings(&app, &settings)?;
+
+        for entry in entries {
+            let _ = std::fs::remove_file(entry.path);
+        }
+
+        Ok(format!(
+            "{} file cache thumbnail dipindah ke {}",
+            copied.len(),
+            target_dir.to_string_lossy()
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,140 @@
+use std::{collections::HashMap, path::Path, sync::OnceLock};
+
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::{
+    models::{UcsCategoryCount, UcsInfo},
+    DbState,
+};
+
+// Tabel CatID -> Category/SubCategory (CatID,Category,SubCategory) dari daftar resmi UCS.
+// Hanya CatID yang ada di tabel yang dianggap UCS, jadi nama file CamelCase biasa
+// seperti "BGMusic_loop.wav" tidak ikut ter-tag.
+const UCS_CATIDS_CSV: &str = include_str!("../resources/ucs_catids.csv");
+
+// Key lowercase supaya "doorwood" dan "DOORWood" sama-sama cocok
+fn ucs_table() -> &'static HashMap<String, (String, String)> {
+    static TABLE: OnceLock<HashMap<String, (String, String)>> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        UCS_CATIDS_CSV
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut cols = line.splitn(3, ',');
+                let (cat_id, category, subcategory) = (cols.next()?, cols.next()?, cols.next()?);
+                Some((
+                    cat_id.to_lowercase(),
+                    (category.to_string(), subcategory.to_string()),
+                ))
+            })
+            .collect()
+    })
+}
+
+// Parse nama file UCS: CatID[-UserCategory]_FXName_CreatorID_SourceID[_UserData]
+pub fn parse_ucs_filename(filename: &str) -> Option<UcsInfo> {
+    let stem = Path::new(filename).file_stem()?.to_string_lossy();
+    let parts: Vec<&str> = stem.split('_').collect();
+
+    if parts.len() < 2 || parts[1].is_empty() {
+        return None;
+    }
+
+    let cat_id = parts[0].split('-').next()?;
+    let (category, subcategory) = ucs_table().get(&cat_id.to_lowercase())?;
+
+    let non_empty = |s: Option<&&str>| s.filter(|s| !s.is_empty()).map(|s| s.to_string());
+
+    Some(UcsInfo {
+        cat_id: cat_id.to_string(),
+        category: Some(category.clone()),
+        subcategory: Some(subcategory.clone()),
+        fx_name: parts[1].to_string(),
+        creator_id: non_empty(parts.get(2)),
+        source_id: non_empty(parts.get(3)),
+    })
+}
+
+// Isi (atau kosongkan) kolom UCS untuk asset di path ini berdasarkan nama filenya
+pub fn apply_ucs_fields(conn: &Connection, path: &str, filename: &str) -> Result<(), String> {
+    let ucs = parse_ucs_filename(filename);
+
+    conn.execute(
+        "UPDATE assets SET ucs_cat_id = ?1, ucs_category = ?2, ucs_subcategory = ?3,
+                ucs_fx_name = ?4, ucs_creator_id = ?5, ucs_source_id = ?6
+         WHERE original_path = ?7",
+        rusqlite::params![
+            ucs.as_ref().map(|u| &u.cat_id),
+            ucs.as_ref().map(|u| &u.category),
+            ucs.as_ref().and_then(|u| u.subcategory.as_ref()),
+            ucs.as_ref().map(|u| &u.fx_name),
+            ucs.as_ref().and_then(|u| u.creator_id.as_ref()),
+            ucs.as_ref().and_then(|u| u.source_id.as_ref()),
+            path,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// Daftar kategori/subkategori yang ada di library beserta jumlah asset-nya
+#[tauri::command]
+pub fn get_ucs_categories(state: State<'_, DbState>) -> Result<Vec<UcsCategoryCount>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT ucs_category, ucs_subcategory, COUNT(*) FROM assets
+             WHERE ucs_category IS NOT NULL
+             GROUP BY ucs_category, ucs_subcategory
+             ORDER BY ucs_category ASC, ucs_subcategory ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(UcsCategoryCount {
+                category: row.get(0)?,
+                subcategory: row.get(1)?,
+                count: row.get::<_, i64>(2)? as u64,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_ucs_filename() {
+        let ucs = parse_ucs_filename("DOORWood-Barn_Creak Open Slow_JD_FarmLib_take2.wav").unwrap();
+        assert_eq!(ucs.cat_id, "DOORWood");
+        assert_eq!(ucs.category.as_deref(), Some("DOORS"));
+        assert_eq!(ucs.subcategory.as_deref(), Some("WOOD"));
+        assert_eq!(ucs.fx_name, "Creak Open Slow");
+        assert_eq!(ucs.creator_id.as_deref(), Some("JD"));
+        assert_eq!(ucs.source_id.as_deref(), Some("FarmLib"));
+    }
+
+    #[test]
+    fn cat_id_lookup_is_case_insensitive() {
+        let ucs = parse_ucs_filename("weapswrd_Slash.wav").unwrap();
+        assert_eq!(ucs.category.as_deref(), Some("WEAPONS"));
+        assert_eq!(ucs.creator_id, None);
+    }
+
+    #[test]
+    fn rejects_unknown_cat_ids_and_plain_names() {
+        assert!(parse_ucs_filename("BGMusic_loop.wav").is_none());
+        assert!(parse_ucs_filename("MainTheme_v2.wav").is_none());
+        assert!(parse_ucs_filename("DOORWood.wav").is_none());
+        assert!(parse_ucs_filename("DOORWood_.wav").is_none());
+    }
+}
@@ -1,4 +1,9 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
 
 use tauri::{AppHandle, Manager};
 
@@ -21,10 +26,15 @@ pub fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
 pub fn get_media_type(ext: &str) -> Option<String> {
     match ext.to_lowercase().as_str() {
         // Image
-        "jpg" | "jpeg" | "png" | "webp" | "gif" | "bmp" | "svg" | "ico" => {
+        "jpg" | "jpeg" | "png" | "webp" | "gif" | "bmp" | "svg" | "svgz" | "ico" => {
             Some("image".to_string())
         }
 
+        // Image pro: layered, HDR, HEIF dan camera RAW
+        "tif" | "tiff" | "psd" | "psb" | "exr" | "heic" | "heif" | "avif" | "cr2" | "cr3"
+        | "crw" | "nef" | "nrw" | "arw" | "srf" | "sr2" | "dng" | "raf" | "orf" | "rw2" | "pef"
+        | "srw" | "3fr" | "iiq" | "rwl" => Some("image".to_string()),
+
         // Video
         "mp4" | "mkv" | "mov" | "avi" | "webm" | "flv" | "wmv" => Some("video".to_string()),
 
@@ -36,3 +46,118 @@ pub fn get_media_type(ext: &str) -> Option<String> {
         _ => None, // File lain diabaikan
     }
 }
+
+// Cari binary di folder bin AppData dulu, fallback ke binary di PATH sistem
+fn get_bin_path(app: &AppHandle, name: &str) -> PathBuf {
+    let exe_name = if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+
+    match get_app_data_dir(app) {
+        Ok(bin_dir) if bin_dir.join(&exe_name).exists() => bin_dir.join(exe_name),
+        _ => PathBuf::from(exe_name),
+    }
+}
+
+pub fn get_ffmpeg_path(app: &AppHandle) -> PathBuf {
+    get_bin_path(app, "ffmpeg")
+}
+
+pub fn get_ffprobe_path(app: &AppHandle) -> PathBuf {
+    get_bin_path(app, "ffprobe")
+}
+
+// Command tanpa membuka console window di Windows
+pub fn hidden_command(program: &Path) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(program);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+
+    cmd
+}
+
+// Jalankan ffmpeg secara blocking, return stderr kalau gagal
+pub fn run_ffmpeg(app: &AppHandle, args: &[String]) -> Result<(), String> {
+    run_ffmpeg_output(app, args).map(|_| ())
+}
+
+// Sama seperti run_ffmpeg, tapi return stdout (untuk output pipe:1 / image2pipe)
+pub fn run_ffmpeg_output(app: &AppHandle, args: &[String]) -> Result<Vec<u8>, String> {
+    let output = hidden_command(&get_ffmpeg_path(app))
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(format!(
+            "ffmpeg exited with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+// Sama seperti run_ffmpeg_output, tapi stdout dibaca per blok lewat on_block
+// (untuk output pipe besar seperti PCM mentah, tanpa menampung semuanya di memori)
+pub fn run_ffmpeg_stream(
+    app: &AppHandle,
+    args: &[String],
+    mut on_block: impl FnMut(&[u8]),
+) -> Result<(), String> {
+    let mut child = hidden_command(&get_ffmpeg_path(app))
+        .args(["-hide_banner", "-loglevel", "error", "-y"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+
+    // stderr dibaca di thread lain supaya pipe-nya tidak penuh dan memblok ffmpeg
+    let stderr = child.stderr.take();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+
+    if let Some(mut stdout) = child.stdout.take() {
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match stdout.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => on_block(&buf[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(e.to_string());
+                }
+            }
+        }
+    }
+
+    let status = child.wait().map_err(|e| e.to_string())?;
+    let stderr = stderr_thread.join().unwrap_or_default();
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "ffmpeg exited with status {}: {}",
+            status,
+            stderr.trim()
+        ))
+    }
+}
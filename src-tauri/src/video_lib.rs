@@ -0,0 +1,1060 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use rusqlite::Connection;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::{
+    folder_lib::import_single_file,
+    image_lib::{encode_webp, load_thumbnail_settings, thumbnails_dir},
+    models::{AssetMetadata, ProgressEvent, Region, ScrubStrip, VideoMetadata},
+    sound_lib::{build_export_path, map_region_row, update_waveform_and_metadata},
+    thumb_cache_lib::{asset_cache_key, stored_cache_key},
+    utils::{get_ffprobe_path, hidden_command, run_ffmpeg, run_ffmpeg_output},
+    DbState,
+};
+
+// Posisi poster frame: 10% durasi (lewati intro hitam/fade-in), maksimal 10 detik
+fn poster_time(duration_sec: f64) -> f64 {
+    if duration_sec > 0.0 {
+        (duration_sec * 0.1).min(10.0)
+    } else {
+        1.0
+    }
+}
+
+// Ambil satu frame video sebagai gambar RGBA lewat ffmpeg (PNG via stdout)
+pub fn extract_video_frame(
+    app: &AppHandle,
+    path: &Path,
+    at_sec: f64,
+    filter: Option<&str>,
+) -> Result<image::RgbaImage, String> {
+    let mut args = vec![
+        "-ss".to_string(),
+        format!("{:.3}", at_sec),
+        "-i".to_string(),
+        path.to_string_lossy().to_string(),
+    ];
+    if let Some(filter) = filter {
+        args.extend(["-vf".to_string(), filter.to_string()]);
+    }
+    args.extend(
+        [
+            "-frames:v",
+            "1",
+            "-f",
+            "image2pipe",
+            "-vcodec",
+            "png",
+            "pipe:1",
+        ]
+        .iter()
+        .map(|s| s.to_string()),
+    );
+
+    let png = run_ffmpeg_output(app, &args)?;
+    if png.is_empty() {
+        return Err("ffmpeg tidak menghasilkan frame".to_string());
+    }
+
+    image::load_from_memory(&png)
+        .map(|img| img.to_rgba8())
+        .map_err(|e| e.to_string())
+}
+
+// Poster frame representatif: filter `thumbnail` memilih frame paling "khas" dari
+// beberapa frame di sekitar posisi poster. Clip pendek fallback ke frame pertama.
+pub fn extract_poster_frame(
+    app: &AppHandle,
+    path: &Path,
+    duration_sec: f64,
+) -> Result<image::RgbaImage, String> {
+    extract_video_frame(app, path, poster_time(duration_sec), Some("thumbnail=50"))
+        .or_else(|_| extract_video_frame(app, path, 0.0, None))
+}
+
+// "30000/1001" -> 29.97
+fn parse_rate(rate: &str) -> Option<f32> {
+    let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+    let (num, den) = (num.parse::<f32>().ok()?, den.parse::<f32>().ok()?);
+    (den > 0.0 && num > 0.0).then(|| num / den)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty() && *s != "unknown")
+        .map(|s| s.to_string())
+}
+
+// ffprobe menulis angka sebagai string ("bit_rate": "1234567")
+fn num_field(value: &Value, key: &str) -> Option<f64> {
+    match value.get(key)? {
+        Value::String(s) => s.parse().ok(),
+        v => v.as_f64(),
+    }
+}
+
+// Parse output `ffprobe -show_format -show_streams -print_format json`.
+// Return (metadata, durasi detik).
+pub fn parse_ffprobe_json(probe: &Value) -> Result<(VideoMetadata, f64), String> {
+    let streams = probe
+        .get("streams")
+        .and_then(|s| s.as_array())
+        .ok_or("Output ffprobe tidak valid")?;
+    let format = probe.get("format").cloned().unwrap_or(Value::Null);
+
+    let stream_type = |s: &&Value| {
+        s.get("codec_type")
+            .and_then(|t| t.as_str())
+            .map(|t| t.to_string())
+    };
+    let video = streams
+        .iter()
+        // Cover art (attached_pic) bukan stream video sungguhan
+        .filter(|s| {
+            s.get("disposition")
+                .and_then(|d| d.get("attached_pic"))
+                .and_then(|v| v.as_i64())
+                != Some(1)
+        })
+        .find(|s| stream_type(s).as_deref() == Some("video"))
+        .ok_or("Tidak ada stream video")?;
+    let audio_tracks = streams
+        .iter()
+        .filter(|s| stream_type(s).as_deref() == Some("audio"))
+        .count() as u32;
+
+    let fps = str_field(video, "avg_frame_rate")
+        .and_then(|r| parse_rate(&r))
+        .or_else(|| str_field(video, "r_frame_rate").and_then(|r| parse_rate(&r)))
+        .unwrap_or(0.0);
+
+    // Rotasi: ffmpeg baru lewat display matrix (side_data), versi lama lewat tag "rotate"
+    let rotation = video
+        .get("side_data_list")
+        .and_then(|l| l.as_array())
+        .and_then(|l| l.iter().find_map(|d| num_field(d, "rotation")))
+        .map(|r| -r)
+        .or_else(|| video.get("tags").and_then(|t| num_field(t, "rotate")))
+        .map(|r| (r.round() as i32).rem_euclid(360))
+        .unwrap_or(0);
+
+    let bitrate = num_field(&format, "bit_rate")
+        .or_else(|| num_field(video, "bit_rate"))
+        .map(|b| (b / 1000.0).round() as u32);
+
+    let creation_time = format
+        .get("tags")
+        .and_then(|t| str_field(t, "creation_time"))
+        .or_else(|| {
+            video
+                .get("tags")
+                .and_then(|t| str_field(t, "creation_time"))
+        });
+
+    let duration = num_field(&format, "duration")
+        .or_else(|| num_field(video, "duration"))
+        .unwrap_or(0.0);
+
+    let metadata = VideoMetadata {
+        width: num_field(video, "width").unwrap_or(0.0) as u32,
+        height: num_field(video, "height").unwrap_or(0.0) as u32,
+        fps,
+        codec: str_field(video, "codec_name"),
+        bitrate,
+        pixel_format: str_field(video, "pix_fmt"),
+        color_space: str_field(video, "color_space"),
+        audio_tracks,
+        rotation,
+        creation_time,
+    };
+
+    Ok((metadata, duration))
+}
+
+pub fn probe_video(app: &AppHandle, path: &Path) -> Result<(VideoMetadata, f64), String> {
+    let output = hidden_command(&get_ffprobe_path(app))
+        .args([
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+        ])
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe exited with status {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let probe: Value = serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    parse_ffprobe_json(&probe)
+}
+
+// Probe lalu simpan metadata & durasi ke DB
+pub fn probe_and_store(
+    app: &AppHandle,
+    conn: &Arc<Mutex<Connection>>,
+    asset_id: i64,
+    path: &str,
+) -> Result<(VideoMetadata, f64), String> {
+    let (metadata, duration) = probe_video(app, Path::new(path))?;
+    let metadata_json = serde_json::to_string(&AssetMetadata::Video(Box::new(metadata.clone())))
+        .map_err(|e| e.to_string())?;
+
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE assets SET metadata = ?1, duration_sec = ?2 WHERE id = ?3",
+        rusqlite::params![metadata_json, duration, asset_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok((metadata, duration))
+}
+
+#[tauri::command]
+pub fn generate_video_metadata(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    overwrite: bool,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let filter = if overwrite {
+            ""
+        } else {
+            "AND (metadata IS NULL OR metadata IN ('{}', 'null') OR duration_sec IS NULL OR duration_sec = 0)"
+        };
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT id, original_path, filename FROM assets WHERE type = 'video' {}",
+                filter
+            ))
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Metadata semua video sudah lengkap.".to_string());
+    }
+
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, path, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "video-metadata-progress",
+                ProgressEvent {
+                    name: "Video".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            if let Err(e) = probe_and_store(&app, &db_arc, *id, path) {
+                println!("Gagal membaca metadata video {}: {}", filename, e);
+            }
+        });
+
+        let _ = app.emit(
+            "video-metadata-progress",
+            ProgressEvent {
+                name: "Video".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai pembacaan metadata untuk {} video...",
+        total_files
+    ))
+}
+
+const DEFAULT_STRIP_FRAMES: u32 = 20;
+const STRIP_COLUMNS: u32 = 10;
+const STRIP_FRAME_WIDTH: u32 = 160;
+
+const DEFAULT_SHEET_COLUMNS: u32 = 4;
+const DEFAULT_SHEET_ROWS: u32 = 5;
+const DEFAULT_SHEET_FRAME_WIDTH: u32 = 320;
+
+// Sprite & index disimpan di sebelah thumbnail: {key}_strip.webp + {key}_strip.json
+fn strip_paths(app: &AppHandle, key: &str) -> Result<(PathBuf, PathBuf), String> {
+    let dir = thumbnails_dir(app)?;
+
+    Ok((
+        dir.join(format!("{}_strip.webp", key)),
+        dir.join(format!("{}_strip.json", key)),
+    ))
+}
+
+// Filter ffmpeg: ambil `frames` frame merata sepanjang durasi, susun jadi grid.
+// Frame ke-i diambil di detik i * duration / frames.
+fn tile_filter(
+    frames: u32,
+    duration: f64,
+    columns: u32,
+    frame_width: u32,
+    tile_opts: &str,
+) -> String {
+    let columns = columns.clamp(1, frames);
+    let rows = frames.div_ceil(columns);
+    format!(
+        "fps={}/{:.3},scale={}:-2,tile={}x{}{}",
+        frames, duration, frame_width, columns, rows, tile_opts
+    )
+}
+
+fn video_duration(
+    app: &AppHandle,
+    conn: &Arc<Mutex<Connection>>,
+    asset_id: i64,
+) -> Result<(String, f64), String> {
+    let (path, duration): (String, f64) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT original_path, COALESCE(duration_sec, 0) FROM assets
+             WHERE id = ?1 AND type = 'video'",
+            [asset_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Asset tidak ditemukan: {}", e))?
+    };
+
+    if duration > 0.0 {
+        return Ok((path, duration));
+    }
+
+    let (_, duration) = probe_and_store(app, conn, asset_id, &path)?;
+    if duration <= 0.0 {
+        return Err("Durasi video tidak diketahui".to_string());
+    }
+    Ok((path, duration))
+}
+
+pub fn generate_scrub_strip(
+    app: &AppHandle,
+    key: &str,
+    path: &str,
+    duration: f64,
+    frames: u32,
+) -> Result<ScrubStrip, String> {
+    let frames = frames.max(1);
+    let columns = STRIP_COLUMNS.min(frames);
+    let rows = frames.div_ceil(columns);
+
+    let args: Vec<String> = [
+        "-i",
+        path,
+        "-vf",
+        &tile_filter(frames, duration, columns, STRIP_FRAME_WIDTH, ""),
+        "-frames:v",
+        "1",
+        "-f",
+        "image2pipe",
+        "-vcodec",
+        "png",
+        "pipe:1",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let png = run_ffmpeg_output(app, &args)?;
+    let sprite = image::load_from_memory(&png)
+        .map_err(|e| e.to_string())?
+        .to_rgba8();
+
+    let (sprite_path, index_path) = strip_paths(app, key)?;
+    let quality = load_thumbnail_settings(app).quality;
+    let webp = encode_webp(sprite.as_raw(), sprite.width(), sprite.height(), quality)?;
+    std::fs::write(&sprite_path, webp).map_err(|e| e.to_string())?;
+
+    let strip = ScrubStrip {
+        sprite_path: sprite_path.to_string_lossy().to_string(),
+        frame_width: sprite.width() / columns,
+        frame_height: sprite.height() / rows,
+        columns,
+        timestamps: (0..frames)
+            .map(|i| i as f64 * duration / frames as f64)
+            .collect(),
+    };
+
+    let index = serde_json::to_string(&strip).map_err(|e| e.to_string())?;
+    std::fs::write(&index_path, index).map_err(|e| e.to_string())?;
+
+    Ok(strip)
+}
+
+// asset_ids kosong = semua video yang belum punya scrub strip
+#[tauri::command]
+pub fn generate_scrub_strips(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Option<Vec<i64>>,
+    frame_count: Option<u32>,
+    overwrite: bool,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let frames = frame_count.unwrap_or(DEFAULT_STRIP_FRAMES);
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT id, filename, thumbnail_key FROM assets WHERE type = 'video'")
+            .map_err(|e| e.to_string())?;
+
+        let rows: Vec<(i64, String, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        rows.into_iter()
+            .filter(|(id, _, _)| asset_ids.as_ref().is_none_or(|ids| ids.contains(id)))
+            .filter(|(_, _, key)| {
+                overwrite
+                    || !key
+                        .as_deref()
+                        .and_then(|key| strip_paths(&app, key).ok())
+                        .is_some_and(|(sprite, index)| sprite.exists() && index.exists())
+            })
+            .map(|(id, filename, _)| (id, filename))
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Semua video sudah punya scrub strip.".to_string());
+    }
+
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "scrub-strip-progress",
+                ProgressEvent {
+                    name: "Video".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let result = video_duration(&app, &db_arc, *id).and_then(|(path, duration)| {
+                let key = {
+                    let conn = db_arc.lock().map_err(|e| e.to_string())?;
+                    asset_cache_key(&conn, *id, &path)?
+                };
+                generate_scrub_strip(&app, &key, &path, duration, frames)
+            });
+
+            if let Err(e) = result {
+                println!("Gagal membuat scrub strip untuk {}: {}", filename, e);
+            }
+        });
+
+        let _ = app.emit(
+            "scrub-strip-progress",
+            ProgressEvent {
+                name: "Video".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai pembuatan scrub strip untuk {} video...",
+        total_files
+    ))
+}
+
+#[tauri::command]
+pub fn get_scrub_strip(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+) -> Result<Option<ScrubStrip>, String> {
+    let key = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        stored_cache_key(&conn, asset_id)?
+    };
+    let Some(key) = key else {
+        return Ok(None);
+    };
+
+    let (sprite_path, index_path) = strip_paths(&app, &key)?;
+    if !sprite_path.exists() || !index_path.exists() {
+        return Ok(None);
+    }
+
+    let index = std::fs::read_to_string(index_path).map_err(|e| e.to_string())?;
+    let mut strip: ScrubStrip = serde_json::from_str(&index).map_err(|e| e.to_string())?;
+    // Path di index bisa basi kalau cache sudah dipindah ke folder lain
+    strip.sprite_path = sprite_path.to_string_lossy().to_string();
+    Ok(Some(strip))
+}
+
+// Export contact sheet (grid columns x rows frame) ke output_path; format dari ekstensi (jpg/png/webp)
+#[tauri::command]
+pub async fn export_contact_sheet(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_id: i64,
+    output_path: String,
+    columns: Option<u32>,
+    rows: Option<u32>,
+    frame_width: Option<u32>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let columns = columns.unwrap_or(DEFAULT_SHEET_COLUMNS).max(1);
+    let rows = rows.unwrap_or(DEFAULT_SHEET_ROWS).max(1);
+    let frame_width = frame_width.unwrap_or(DEFAULT_SHEET_FRAME_WIDTH);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (path, duration) = video_duration(&app, &db_arc, asset_id)?;
+
+        let filter = tile_filter(
+            columns * rows,
+            duration,
+            columns,
+            frame_width,
+            ":padding=4:margin=4:color=black",
+        );
+        let args: Vec<String> = [
+            "-i",
+            &path,
+            "-vf",
+            &filter,
+            "-frames:v",
+            "1",
+            "-update",
+            "1",
+            &output_path,
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        run_ffmpeg(&app, &args)?;
+        Ok(output_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+const DEFAULT_SCENE_THRESHOLD: f32 = 0.3;
+// Cut yang lebih rapat dari ini dianggap flash/glitch, bukan pergantian shot
+const MIN_SHOT_SEC: f64 = 0.5;
+
+// Parse output filter `metadata=print`: baris "frame:12 pts:6006 pts_time:0.2002"
+fn parse_cut_times(output: &str) -> Vec<f64> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once("pts_time:"))
+        .filter_map(|(_, time)| time.split_whitespace().next()?.parse::<f64>().ok())
+        .collect()
+}
+
+// Ubah daftar cut jadi shot [0, cut1], [cut1, cut2], ..., [cutN, durasi]
+fn cuts_to_shots(cuts: &[f64], duration: f64) -> Vec<(f64, f64)> {
+    let mut shots = Vec::new();
+    let mut start = 0.0;
+
+    for &cut in cuts {
+        if cut - start < MIN_SHOT_SEC || duration - cut < MIN_SHOT_SEC {
+            continue;
+        }
+        shots.push((start, cut));
+        start = cut;
+    }
+    shots.push((start, duration));
+    shots
+}
+
+// Deteksi cut pakai skor scene ffmpeg (0..1, selisih antar frame) di resolusi kecil
+pub fn detect_scene_cuts(app: &AppHandle, path: &str, threshold: f32) -> Result<Vec<f64>, String> {
+    let filter = format!(
+        "scale=320:-2,select='gt(scene,{:.3})',metadata=print:file=-",
+        threshold
+    );
+    let args: Vec<String> = ["-i", path, "-an", "-vf", &filter, "-f", "null", "-"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    let output = run_ffmpeg_output(app, &args)?;
+    Ok(parse_cut_times(&String::from_utf8_lossy(&output)))
+}
+
+// Ganti semua shot milik video dengan daftar (start, end) baru
+fn replace_shots(
+    conn: &mut Connection,
+    asset_id: i64,
+    shots: &[(f64, f64)],
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM shots WHERE asset_id = ?1", [asset_id])?;
+    {
+        let mut stmt = tx.prepare_cached(
+            "INSERT INTO shots (asset_id, shot_index, start_sec, end_sec)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (index, (start, end)) in shots.iter().enumerate() {
+            stmt.execute(rusqlite::params![asset_id, index as i64, start, end])?;
+        }
+    }
+    tx.commit()
+}
+
+// Simpan hasil deteksi ke tabel shots (satu baris per shot)
+#[tauri::command]
+pub fn detect_scenes(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Vec<i64>,
+    threshold: Option<f32>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+    let threshold = threshold
+        .unwrap_or(DEFAULT_SCENE_THRESHOLD)
+        .clamp(0.01, 1.0);
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT filename FROM assets WHERE id = ?1 AND type = 'video'")
+            .map_err(|e| e.to_string())?;
+
+        asset_ids
+            .iter()
+            .filter_map(|id| {
+                stmt.query_row([id], |row| row.get(0))
+                    .ok()
+                    .map(|filename| (*id, filename))
+            })
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Tidak ada video untuk dideteksi.".to_string());
+    }
+
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "scene-progress",
+                ProgressEvent {
+                    name: "Scene".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let shots = video_duration(&app, &db_arc, *id).and_then(|(path, duration)| {
+                let cuts = detect_scene_cuts(&app, &path, threshold)?;
+                Ok(cuts_to_shots(&cuts, duration))
+            });
+
+            let result = shots.and_then(|shots| {
+                let mut conn = db_arc.lock().map_err(|e| e.to_string())?;
+                replace_shots(&mut conn, *id, &shots).map_err(|e| e.to_string())
+            });
+
+            if let Err(e) = result {
+                println!("Gagal deteksi scene {}: {}", filename, e);
+            }
+        });
+
+        let _ = app.emit(
+            "scene-progress",
+            ProgressEvent {
+                name: "Scene".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai deteksi scene untuk {} video...",
+        total_files
+    ))
+}
+
+// Shot milik satu video, bentuknya sama dengan Region supaya UI bisa memakai ulang
+// komponen daftar region
+#[tauri::command]
+pub fn get_video_shots(state: State<'_, DbState>, asset_id: i64) -> Result<Vec<Region>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.id, s.asset_id, s.shot_index AS region_index, s.start_sec, s.end_sec,
+                    a.filename, a.extension, a.original_path
+             FROM shots s JOIN assets a ON a.id = s.asset_id
+             WHERE s.asset_id = ?1 ORDER BY s.shot_index ASC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([asset_id], map_region_row)
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+// Export satu shot jadi file terpisah. Default stream copy tanpa re-encode
+// (awal clip menempel ke keyframe terdekat); `accurate` memaksa re-encode agar frame-akurat.
+// Kalau stream copy gagal (container tidak mendukung), otomatis fallback ke re-encode.
+#[tauri::command]
+pub async fn export_video_subclip(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    shot_id: i64,
+    output_dir: Option<String>,
+    accurate: Option<bool>,
+) -> Result<String, String> {
+    let (path, extension, shot_index, start, end): (String, String, i64, f64, f64) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT a.original_path, a.extension, s.shot_index, s.start_sec, s.end_sec
+             FROM shots s JOIN assets a ON a.id = s.asset_id
+             WHERE s.id = ?1",
+            [shot_id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .map_err(|e| format!("Shot tidak ditemukan: {}", e))?
+    };
+
+    let ext = extension.to_lowercase();
+    let suffix = format!("shot{:02}", shot_index + 1);
+    let out_path = build_export_path(&path, output_dir, &suffix, &ext)?
+        .to_string_lossy()
+        .to_string();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let seek = |codec: &[&str]| -> Vec<String> {
+            let mut args: Vec<String> = [
+                "-ss",
+                &format!("{:.3}", start),
+                "-i",
+                &path,
+                "-t",
+                &format!("{:.3}", end - start),
+                "-map",
+                "0",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+            args.extend(codec.iter().map(|s| s.to_string()));
+            args.push(out_path.clone());
+            args
+        };
+
+        let copy = seek(&["-c", "copy", "-avoid_negative_ts", "make_zero"]);
+        let reencode = seek(&[
+            "-c:v", "libx264", "-preset", "fast", "-crf", "18", "-c:a", "aac", "-b:a", "192k",
+        ]);
+
+        if accurate.unwrap_or(false) {
+            run_ffmpeg(&app, &reencode)?;
+        } else if let Err(e) = run_ffmpeg(&app, &copy) {
+            println!("Stream copy gagal, re-encode: {}", e);
+            run_ffmpeg(&app, &reencode)?;
+        }
+
+        Ok(out_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Extract stream audio video jadi file WAV/FLAC, import sebagai asset audio yang
+// terhubung ke video sumbernya (source_asset_id), lalu langsung generate waveform.
+// all_streams = false hanya mengambil stream audio pertama.
+#[tauri::command]
+pub fn extract_audio_from_videos(
+    app: AppHandle,
+    state: State<'_, DbState>,
+    asset_ids: Vec<i64>,
+    format: String,
+    all_streams: bool,
+    output_dir: Option<String>,
+) -> Result<String, String> {
+    let db_arc = state.conn.clone();
+
+    let format = format.to_lowercase();
+    let codec = match format.as_str() {
+        "wav" => "pcm_s24le",
+        "flac" => "flac",
+        other => return Err(format!("Format {} tidak didukung (wav/flac)", other)),
+    };
+
+    state.cancel_scan.store(false, Ordering::SeqCst);
+    let cancel_flag = state.cancel_scan.clone();
+
+    let to_process: Vec<(i64, String, String)> = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT original_path, filename FROM assets WHERE id = ?1 AND type = 'video'")
+            .map_err(|e| e.to_string())?;
+
+        asset_ids
+            .iter()
+            .filter_map(|id| {
+                stmt.query_row([id], |row| Ok((*id, row.get(0)?, row.get(1)?)))
+                    .ok()
+            })
+            .collect()
+    };
+
+    let total_files = to_process.len();
+    if total_files == 0 {
+        return Ok("Tidak ada video untuk di-extract.".to_string());
+    }
+
+    let processed_count = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn(move || {
+        to_process.par_iter().for_each(|(id, path, filename)| {
+            if cancel_flag.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
+
+            let _ = app.emit(
+                "extract-audio-progress",
+                ProgressEvent {
+                    name: "Extract".to_string(),
+                    current,
+                    total: total_files,
+                    filename: filename.clone(),
+                    status: "processing".to_string(),
+                },
+            );
+
+            let tracks = match probe_video(&app, Path::new(path)) {
+                Ok((metadata, _)) => metadata.audio_tracks,
+                Err(e) => {
+                    println!("Gagal membaca stream {}: {}", filename, e);
+                    return;
+                }
+            };
+            let count = if all_streams { tracks } else { tracks.min(1) };
+
+            for stream in 0..count {
+                let suffix = if count > 1 {
+                    format!("audio{:02}", stream + 1)
+                } else {
+                    "audio".to_string()
+                };
+
+                let result = build_export_path(path, output_dir.clone(), &suffix, &format)
+                    .and_then(|out_path| {
+                        let args: Vec<String> = [
+                            "-i",
+                            path,
+                            "-map",
+                            &format!("0:a:{}", stream),
+                            "-vn",
+                            "-c:a",
+                            codec,
+                            &out_path.to_string_lossy(),
+                        ]
+                        .iter()
+                        .map(|s| s.to_string())
+                        .collect();
+
+                        run_ffmpeg(&app, &args)?;
+                        import_extracted_audio(&db_arc, *id, &out_path)
+                    });
+
+                if let Err(e) = result {
+                    println!("Gagal extract audio {} stream {}: {}", filename, stream, e);
+                }
+            }
+        });
+
+        let _ = app.emit(
+            "extract-audio-progress",
+            ProgressEvent {
+                name: "Extract".to_string(),
+                current: total_files,
+                total: total_files,
+                filename: "Selesai!".to_string(),
+                status: "done".to_string(),
+            },
+        );
+    });
+
+    Ok(format!(
+        "Memulai extract audio dari {} video...",
+        total_files
+    ))
+}
+
+fn import_extracted_audio(
+    db_arc: &Arc<Mutex<Connection>>,
+    source_id: i64,
+    out_path: &Path,
+) -> Result<(), String> {
+    import_single_file(db_arc, out_path)?;
+
+    let out_str = out_path.to_string_lossy().to_string();
+    let (id, filename, file_size): (i64, String, i64) = {
+        let conn = db_arc.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE assets SET source_asset_id = ?1 WHERE original_path = ?2",
+            rusqlite::params![source_id, out_str],
+        )
+        .map_err(|e| e.to_string())?;
+
+        conn.query_row(
+            "SELECT id, filename, file_size FROM assets WHERE original_path = ?1",
+            [&out_str],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?
+    };
+
+    update_waveform_and_metadata(db_arc, id, &out_str, &filename, file_size);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ffprobe_json_skips_cover_art_and_counts_audio() {
+        let probe = json!({
+            "streams": [
+                { "codec_type": "video", "codec_name": "mjpeg", "disposition": { "attached_pic": 1 } },
+                {
+                    "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                    "avg_frame_rate": "0/0", "r_frame_rate": "30000/1001", "pix_fmt": "yuv420p",
+                    "side_data_list": [{ "rotation": -90 }]
+                },
+                { "codec_type": "audio" },
+                { "codec_type": "audio" }
+            ],
+            "format": { "duration": "12.500", "bit_rate": "8000000", "tags": { "creation_time": "2024-01-02T03:04:05Z" } }
+        });
+
+        let (meta, duration) = parse_ffprobe_json(&probe).unwrap();
+        assert_eq!(meta.codec.as_deref(), Some("h264"));
+        assert_eq!((meta.width, meta.height), (1920, 1080));
+        assert!((meta.fps - 29.97).abs() < 0.01);
+        assert_eq!(meta.rotation, 90);
+        assert_eq!(meta.bitrate, Some(8000));
+        assert_eq!(meta.audio_tracks, 2);
+        assert_eq!(meta.creation_time.as_deref(), Some("2024-01-02T03:04:05Z"));
+        assert_eq!(duration, 12.5);
+    }
+
+    #[test]
+    fn ffprobe_json_without_video_stream_fails() {
+        let probe = json!({ "streams": [{ "codec_type": "audio" }], "format": {} });
+        assert!(parse_ffprobe_json(&probe).is_err());
+    }
+
+    #[test]
+    fn cuts_to_shots_covers_whole_duration() {
+        assert_eq!(cuts_to_shots(&[], 10.0), vec![(0.0, 10.0)]);
+        assert_eq!(
+            cuts_to_shots(&[2.0, 5.0], 10.0),
+            vec![(0.0, 2.0), (2.0, 5.0), (5.0, 10.0)]
+        );
+    }
+
+    #[test]
+    fn cuts_to_shots_drops_cuts_that_make_tiny_shots() {
+        // 2.2 terlalu dekat dengan 2.0, 9.8 terlalu dekat dengan akhir video
+        assert_eq!(
+            cuts_to_shots(&[0.1, 2.0, 2.2, 9.8], 10.0),
+            vec![(0.0, 2.0), (2.0, 10.0)]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 import useAssetStore from "@/stores/asset-store";
-import { convertFileSrc } from "@tauri-apps/api/core";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 import { useEffect, useRef, useState } from "react";
 import { Input } from "@/components/ui/input";
 import { Search, LayoutList, LayoutGrid, Maximize2, ZoomIn, Settings2 } from "lucide-react";
@@ -145,6 +145,18 @@ const ImagePage = () => {
         );
     };
 
+    // Thumbnail yang sudah di-evict dari cache dibuat ulang oleh backend saat diminta
+    const restoreThumbnail = async (img: HTMLImageElement, file: Asset) => {
+        if (!file.id || img.dataset.restored) return;
+        img.dataset.restored = "true";
+        try {
+            const path = await invoke<string | null>("get_thumbnail_path", { assetId: file.id, size: 256 });
+            if (path) img.src = `${convertFileSrc(path)}?v=${Date.now()}`;
+        } catch (error) {
+            console.error("Error restoring thumbnail:", error);
+        }
+    };
+
     const renderImageCard = (file: Asset, imageHeight: string, minHeight: number) => {
         const isHovered = hoveredId === file.id;
         const imageSrc = file.thumbnail_path ? convertFileSrc(file.thumbnail_path) : "";
@@ -163,6 +175,7 @@ const ImagePage = () => {
                         className={`w-full ${imageHeight} object-cover bg-muted`}
                         loading="lazy"
                         decoding="async"
+                        onError={(e) => restoreThumbnail(e.currentTarget, file)}
                     />
 
                     {/* Hover Overlay */}
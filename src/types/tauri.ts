@@ -10,9 +10,61 @@ export interface AssetMetadata {
   height?: number;
   frame_rate?: number;
   color_space?: string;
+  // Video (ffprobe)
+  fps?: number;
+  pixel_format?: string;
+  audio_tracks?: number;
+  rotation?: number; // Degrees clockwise
+  creation_time?: string;
+  // Image
+  format?: string;
+  layer_count?: number; // PSD/PSB layers, EXR layers
+  page_count?: number; // Multi-page TIFF
+  frame_count?: number; // Animated GIF/WebP/APNG
+  loop_count?: number; // 0 = loops forever
+  duration_sec?: number;
+  // Image (EXIF / IPTC / XMP)
+  camera_make?: string;
+  camera_model?: string;
+  lens?: string;
+  iso?: number;
+  shutter_speed?: string;
+  aperture?: number;
+  focal_length_mm?: number;
+  capture_date?: string;
+  gps_latitude?: number;
+  gps_longitude?: number;
+  gps_altitude?: number;
+  orientation?: number;
+  copyright?: string;
+  rating?: number;
+  // Embedded audio metadata (ID3, Vorbis, RIFF INFO, BWF bext, iXML)
+  title?: string;
+  artist?: string;
+  description?: string;
+  keywords?: string[];
+  originator?: string;
+  origination_date?: string;
+  time_reference?: number;
   [key: string]: any; // For flexible metadata
 }
 
+// Parsed Universal Category System filename
+export interface UcsInfo {
+  cat_id: string;
+  category?: string; // Optional because the DB columns are nullable
+  subcategory?: string;
+  fx_name: string;
+  creator_id?: string;
+  source_id?: string;
+}
+
+export interface UcsCategoryCount {
+  category: string;
+  subcategory?: string;
+  count: number;
+}
+
 export interface Asset {
   id?: number; // Optional because it's auto-generated on insert
   filename: string;
@@ -27,13 +79,110 @@ export interface Asset {
   
   // Waveform stored as binary data (array of floats)
   waveform_data?: number[];
+  // Per-bar RMS aligned with waveform_data, shows where the loud/dense parts are
+  waveform_rms?: number[];
   
   // Flexible metadata
   metadata: AssetMetadata;
+
+  // Suggested in/out points from silence detection (seconds)
+  trim_start_sec?: number;
+  trim_end_sec?: number;
+
+  // Present when the filename follows UCS naming
+  ucs?: UcsInfo;
+
+  // Asset this one was derived from (e.g. source video of extracted audio)
+  source_asset_id?: number | null;
+
+  // 0-5, -1 = rejected; seeded from XMP rating for images
+  rating?: number | null;
+
+  // BlurHash placeholder painted until the thumbnail loads
+  blurhash?: string | null;
+}
+
+// Single hit inside a multi-hit audio file
+export interface Region {
+  id: number;
+  asset_id: number;
+  region_index: number;
+  start_sec: number;
+  end_sec: number;
+
+  // Parent asset info so the region can be played directly
+  filename: string;
+  extension: string;
+  original_path: string;
+}
+
+export interface AssetTag {
+  id: number;
+  asset_id: number;
+  tag: string;
+  source: 'auto' | 'user';
+  confidence: number;
+  status: 'suggested' | 'accepted' | 'rejected';
+}
+
+export interface SimilarAsset {
+  asset: Asset;
+  distance: number; // Lower is more similar
+}
+
+// Video sprite sheet for hover scrubbing; frame i sits at
+// column i % columns, row floor(i / columns)
+export interface ScrubStrip {
+  sprite_path: string;
+  frame_width: number;
+  frame_height: number;
+  columns: number;
+  timestamps: number[]; // Seconds
+}
+
+// Dominant image color; weight = share of pixels (0-1)
+export interface PaletteColor {
+  hex: string; // "#rrggbb"
+  weight: number;
+}
+
+export interface ThumbnailSettings {
+  sizes: number[]; // Thumbnail widths in px, e.g. [128, 256, 512]
+  quality: number; // Lossy WebP quality 0-100
+  // Changed via move_thumbnail_cache / set_thumbnail_cache_limit, not regenerate_thumbnails
+  cache_dir?: string | null; // null = app data folder
+  max_cache_mb?: number | null; // null = unlimited
+}
+
+// Result of gc_thumbnail_cache / set_thumbnail_cache_limit
+export interface ThumbnailCacheReport {
+  cache_dir: string;
+  removed_files: number;
+  reclaimed_bytes: number;
+  total_bytes: number; // Cache size after cleanup
+}
+
+export interface PlayablePath {
+  path: string;
+  is_proxy: boolean;
+  needs_proxy: boolean; // A proxy is recommended but not generated yet
+}
+
+// Payload of the 'playback-position' event and playback commands
+export interface PlaybackStatus {
+  asset_id: number | null;
+  region_id: number | null;
+  position_sec: number;
+  duration_sec: number;
+  range_start_sec: number;
+  range_end_sec: number;
+  playing: boolean;
+  looping: boolean;
+  device: string; // 'default', 'null', or a device name
 }
 
-export interface PaginatedResponse {
-  data: Asset[];
+export interface PaginatedResponse<T = Asset> {
+  data: T[];
   total_items: number;
   total_pages: number;
   current_page: number;